@@ -22,6 +22,15 @@ mod table;
 /// - `Vec<u8>` (sqlite `blob`)
 /// - Any table in the same schema (sqlite `integer` with foreign key constraint)
 /// - `Option<T>` where `T` is not an `Option` (sqlite nullable)
+/// - `chrono::DateTime<Utc>` and `chrono::NaiveDateTime` (sqlite `integer` with unix seconds,
+///   requires the `chrono` feature)
+/// - `chrono::NaiveDate` and `chrono::NaiveTime` (sqlite `text` in ISO 8601 format,
+///   requires the `chrono` feature)
+/// - `time::OffsetDateTime` and `time::Duration` (sqlite `integer` with seconds,
+///   requires the `time` feature)
+/// - `time::Date` and `time::Time` (sqlite `text` in ISO 8601 format, requires the `time` feature)
+/// - `uuid::Uuid` (sqlite `blob` with 16 bytes, requires the `uuid` feature)
+/// - `rust_decimal::Decimal` (sqlite `text`, requires the `rust_decimal` feature)
 ///
 /// Booleans are not supported in schemas yet.
 ///
@@ -49,6 +58,55 @@ mod table;
 /// The table will also have two unique contraints.
 /// Note that optional types are not allowed in unique constraints.
 ///
+/// Unique constraints that span multiple columns are defined on the table instead.
+/// For every unique constraint, a lookup function with the same name as the attribute is generated.
+/// This function takes one argument per column and returns an optional table row.
+/// ```
+/// # use rust_query::{LocalClient, migration::Config};
+/// #[rust_query::migration::schema(Schema)]
+/// pub mod vN {
+///     #[unique_name(first_name, last_name)]
+///     pub struct User {
+///         pub first_name: String,
+///         pub last_name: String,
+///     }
+/// }
+/// # fn main() {
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// let mut txn = client.transaction_mut(&db);
+/// let alice = txn.insert(v0::User { first_name: "Alice", last_name: "Smith" }).unwrap();
+/// let found = txn.query_one(v0::User::unique_name("Alice", "Smith"));
+/// assert_eq!(found, Some(alice));
+/// # }
+/// ```
+///
+/// ## Indexes
+///
+/// Columns that are often filtered on can be given an index with the `#[index]` attribute.
+/// The index is created together with the table and is named after the table and column,
+/// e.g. `user_score_index`.
+/// Using `#[index(unique)]` is the same as using `#[unique_<column>]` on the field.
+///
+/// ```
+/// # use rust_query::{LocalClient, migration::Config};
+/// #[rust_query::migration::schema(Schema)]
+/// pub mod vN {
+///     pub struct User {
+///         #[index(unique)]
+///         pub email: String,
+///         #[index]
+///         pub score: i64,
+///     }
+/// }
+/// # fn main() {
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let txn = client.transaction(&db);
+/// # let _ = txn.query_one(v0::User::unique_email("test"));
+/// # }
+/// ```
+///
 /// ## Multiple versions
 /// The macro uses enum syntax, but it generates multiple modules of types.
 ///
@@ -200,11 +258,78 @@ mod table;
 /// #     m.finish().expect("database version is after supported versions")
 /// # }
 /// ```
+/// This is also the way to copy data to a restructured table. The migration closure
+/// receives the columns of the old table and must provide the columns of the new table,
+/// so the mapping between the two tables is type checked.
+///
+/// # `#[rename_from = "..."]` Attribute
+/// You can use this attribute when renaming a column.
+/// The new column gets a version range that starts where the old column stops,
+/// and its values are copied from the old column in the migration.
+/// This means that no value needs to be provided in the `migrate` struct for the new column.
+/// The old and new column need to have the same type.
+/// ```
+/// # use rust_query::migration::{schema, Config};
+/// # use rust_query::{LocalClient, Table};
+/// #[schema(Schema)]
+/// #[version(0..=1)]
+/// pub mod vN {
+///     pub struct User {
+///         #[version(..1)]
+///         pub name: String,
+///         #[version(1..)]
+///         #[rename_from = "name"]
+///         pub full_name: String,
+///     }
+/// }
+/// # fn main() {
+/// # let mut client = LocalClient::try_new().unwrap();
+/// let config = Config::open_in_memory().init_stmt("INSERT INTO user (name) VALUES ('Alice')");
+/// let m = client.migrator(config).unwrap();
+/// let m = m.migrate(|txn| v0::migrate::Schema {
+///     user: txn.migrate_ok(|()| v0::migrate::User {}),
+/// });
+/// let db = m.finish().unwrap();
+/// let txn = client.transaction(&db);
+/// let names = txn.query(|rows| {
+///     let user = v1::User::join(rows);
+///     rows.into_vec(user.full_name())
+/// });
+/// assert_eq!(names, ["Alice"]);
+/// # }
+/// ```
 ///
 /// # `#[no_reference]` Attribute
 /// You can put this attribute on your table definitions and it will make it impossible
 /// to have foreign key references to such table.
 /// This makes it possible to use `TransactionWeak::delete_ok`.
+///
+/// # `#[table(name = "...")]` and `#[column(name = "...")]` Attributes
+/// By default the sql name of a table is the struct name in snake case and the sql name of a column
+/// is the field name. These attributes override the sql names, which is useful for databases
+/// that use different naming conventions. The rust names are still used everywhere else.
+/// ```
+/// # use rust_query::{LocalClient, migration::Config};
+/// #[rust_query::migration::schema(Schema)]
+/// pub mod vN {
+///     #[table(name = "tbl_Artist")]
+///     pub struct Artist {
+///         #[column(name = "ArtistName")]
+///         pub name: String,
+///     }
+/// }
+/// use v0::*;
+/// # fn main() {
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&db);
+/// txn.insert_ok(Artist { name: "AC/DC" });
+/// let names = txn
+///     .query_raw("SELECT ArtistName FROM tbl_Artist", (), |row| row.get::<_, String>(0))
+///     .unwrap();
+/// assert_eq!(names, ["AC/DC"]);
+/// # }
+/// ```
 #[proc_macro_attribute]
 pub fn schema(
     attr: proc_macro::TokenStream,
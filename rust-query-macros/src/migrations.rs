@@ -93,10 +93,20 @@ fn define_table_migration(
     let mut migration_conflict = quote! {::std::convert::Infallible};
     let mut conflict_from = quote! {::std::unreachable!()};
 
+    let mut renamed = false;
     for (i, col) in &table.columns {
         let name = &col.name;
         if prev_columns.contains_key(i) {
             col_new.push(quote! {prev.#name()});
+        } else if let Some(old) = &col.rename_from {
+            if !prev_columns.values().any(|prev| &prev.name == old) {
+                return Err(syn::Error::new_spanned(
+                    old,
+                    "the column does not exist in the previous version",
+                ));
+            }
+            col_new.push(quote! {prev.#old()});
+            renamed = true;
         } else {
             let mut unique_columns = table.uniques.iter().flat_map(|u| &u.columns);
             if unique_columns.any(|c| c == name) {
@@ -114,7 +124,11 @@ fn define_table_migration(
 
     // check that nothing was added or removed
     // we don't need input if only stuff was removed, but it still needs migrating
-    if !always_migrate && alter_ident.is_empty() && table.columns.len() == prev_columns.len() {
+    if !always_migrate
+        && !renamed
+        && alter_ident.is_empty()
+        && table.columns.len() == prev_columns.len()
+    {
         return Ok(None);
     }
 
@@ -24,12 +24,19 @@ pub(crate) struct VersionedTable {
     pub uniques: Vec<Unique>,
     pub columns: Vec<VersionedColumn>,
     pub referenceable: bool,
+    // name of the table in sql
+    pub sql_name: String,
 }
 
 pub(crate) struct VersionedColumn {
     pub versions: std::ops::Range<u32>,
     pub name: Ident,
     pub typ: TokenStream,
+    pub index: bool,
+    // name of the column in sql
+    pub sql_name: String,
+    // column in the previous version that this column is copied from
+    pub rename_from: Option<Ident>,
 }
 
 impl VersionedSchema {
@@ -54,7 +61,12 @@ impl VersionedSchema {
                     SingleVersionColumn {
                         name: c.name.clone(),
                         typ: c.typ.clone(),
+                        index: c.index,
+                        sql_name: c.sql_name.clone(),
                         is_def: version == c.versions.end - 1,
+                        rename_from: (version == c.versions.start)
+                            .then(|| c.rename_from.clone())
+                            .flatten(),
                     },
                 );
             }
@@ -77,6 +89,7 @@ impl VersionedSchema {
             uniques: table.uniques.clone(),
             columns,
             referenceable: table.referenceable,
+            sql_name: table.sql_name.clone(),
         })
     }
 }
@@ -87,11 +100,16 @@ pub(crate) struct SingleVersionTable {
     pub uniques: Vec<Unique>,
     pub columns: BTreeMap<usize, SingleVersionColumn>,
     pub referenceable: bool,
+    pub sql_name: String,
 }
 
 pub(crate) struct SingleVersionColumn {
     pub name: Ident,
     pub typ: TokenStream,
+    pub index: bool,
+    pub sql_name: String,
     // is this the latest version where the column exists?
     pub is_def: bool,
+    // only set in the first version where the column exists
+    pub rename_from: Option<Ident>,
 }
@@ -1,7 +1,8 @@
 use std::ops::{Not, Range};
 
-use quote::ToTokens;
-use syn::{punctuated::Punctuated, Attribute, Field, Ident, Item, Path, Token, Visibility};
+use heck::ToSnekCase;
+use quote::{format_ident, ToTokens};
+use syn::{punctuated::Punctuated, Attribute, Field, Ident, Item, LitStr, Path, Token, Visibility};
 
 use crate::multi::{Unique, VersionedColumn, VersionedSchema, VersionedTable};
 
@@ -24,6 +25,9 @@ impl VersionedColumn {
         }
 
         let mut other_field_attr = vec![];
+        let mut index = false;
+        let mut sql_name = None;
+        let mut rename_from = None;
         for attr in field.attrs.clone() {
             if let Some(unique) = is_unique(attr.path()) {
                 attr.meta.require_path_only()?;
@@ -31,18 +35,76 @@ impl VersionedColumn {
                     name: unique,
                     columns: vec![name.clone()],
                 })
+            } else if attr.path().is_ident("index") {
+                if let syn::Meta::Path(_) = attr.meta {
+                    index = true;
+                } else {
+                    let kind: Ident = attr.parse_args()?;
+                    if kind != "unique" {
+                        return Err(syn::Error::new_spanned(kind, "expected `unique`"));
+                    }
+                    uniques.push(Unique {
+                        name: format_ident!("unique_{name}"),
+                        columns: vec![name.clone()],
+                    })
+                }
+            } else if attr.path().is_ident("column") {
+                if sql_name.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "can not have multiple `name` attributes",
+                    ));
+                }
+                let lit = parse_name(&attr)?;
+                if lit.value().to_lowercase() == "id" {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "The `id` column is reserved to be used by rust-query internally",
+                    ));
+                }
+                sql_name = Some(lit.value());
+            } else if attr.path().is_ident("rename_from") {
+                if rename_from.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "can not have multiple rename_from",
+                    ));
+                }
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) = &attr.meta.require_name_value()?.value
+                else {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "expected `rename_from = \"...\"`",
+                    ));
+                };
+                rename_from = Some(lit.parse::<Ident>()?);
             } else {
                 other_field_attr.push(attr);
             }
         }
         let versions = parse_version(&other_field_attr)?
             .unwrap_or_default()
-            .into_std(limit, true)?;
+            .into_std(limit.clone(), true)?;
+
+        if let Some(rename_from) = &rename_from {
+            if versions.start == limit.start {
+                return Err(syn::Error::new_spanned(
+                    rename_from,
+                    "rename_from requires a version range that starts after the table",
+                ));
+            }
+        }
 
         Ok(VersionedColumn {
             versions,
+            sql_name: sql_name.unwrap_or_else(|| name.to_string()),
             name,
             typ: field.ty.into_token_stream(),
+            index,
+            rename_from,
         })
     }
 }
@@ -57,6 +119,7 @@ impl VersionedTable {
         let mut uniques = vec![];
         let mut prev = None;
         let mut referenceable = true;
+        let mut sql_name = None;
 
         for attr in &table.attrs {
             if let Some(unique) = is_unique(attr.path()) {
@@ -73,6 +136,14 @@ impl VersionedTable {
                     return Err(syn::Error::new_spanned(attr, "can not have multiple from"));
                 }
                 prev = Some(attr.parse_args()?)
+            } else if attr.path().is_ident("table") {
+                if sql_name.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "can not have multiple `name` attributes",
+                    ));
+                }
+                sql_name = Some(parse_name(attr)?.value());
             } else {
                 other_attrs.push(attr.clone());
             }
@@ -98,6 +169,7 @@ impl VersionedTable {
         Ok(VersionedTable {
             versions,
             prev,
+            sql_name: sql_name.unwrap_or_else(|| table.ident.to_string().to_snek_case()),
             name: table.ident,
             columns,
             uniques,
@@ -235,6 +307,20 @@ fn parse_version(attrs: &[Attribute]) -> syn::Result<Option<MyRange>> {
     Ok(version)
 }
 
+/// Parse an attribute of the form `#[attr(name = "...")]`.
+fn parse_name(attr: &Attribute) -> syn::Result<LitStr> {
+    let mut name = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `name`"))
+        }
+    })?;
+    name.ok_or_else(|| syn::Error::new_spanned(attr, "expected `name = \"...\"`"))
+}
+
 fn is_unique(path: &Path) -> Option<Ident> {
     path.get_ident().and_then(|ident| {
         ident
@@ -3,7 +3,6 @@ use std::collections::BTreeMap;
 use crate::{dummy::wrap, SingleVersionTable};
 
 use super::make_generic;
-use heck::ToSnekCase;
 use quote::{format_ident, quote};
 
 use proc_macro2::{Span, TokenStream};
@@ -20,6 +19,7 @@ pub fn define_all_tables(
 ) -> syn::Result<TokenStream> {
     let mut mod_output = TokenStream::new();
     let mut schema_table_typs = vec![];
+    let mut schema_table_metas = vec![];
     for table in new_tables.values_mut() {
         let table_def = define_table(
             table,
@@ -32,6 +32,7 @@ pub fn define_all_tables(
 
         let table_name = &table.name;
         schema_table_typs.push(quote! {b.table::<#table_name>()});
+        schema_table_metas.push(quote! {::rust_query::migration::TableMeta::new::<#table_name>()});
     }
 
     let version_i64 = version as i64;
@@ -39,11 +40,20 @@ pub fn define_all_tables(
         pub struct #schema_name;
         impl ::rust_query::private::Schema for #schema_name {
             const VERSION: i64 = #version_i64;
+            const TABLES: &'static [::rust_query::migration::TableMeta] = &[
+                #(#schema_table_metas,)*
+            ];
 
             fn typs(b: &mut ::rust_query::private::TableTypBuilder<Self>) {
                 #(#schema_table_typs;)*
             }
         }
+        impl #schema_name {
+            /// All tables in this schema, see [::rust_query::migration::TableMeta].
+            pub const fn tables() -> &'static [::rust_query::migration::TableMeta] {
+                <Self as ::rust_query::private::Schema>::TABLES
+            }
+        }
     });
     Ok(mod_output)
 }
@@ -58,7 +68,7 @@ fn define_table(
     let table_ident_with_span = table.name.clone();
     table.name.set_span(Span::call_site());
     let table_ident = &table.name;
-    let table_name: &String = &table_ident.to_string().to_snek_case();
+    let table_name: &String = &table.sql_name;
 
     let mut unique_typs = vec![];
     let mut unique_funcs = vec![];
@@ -69,10 +79,10 @@ fn define_table(
         let mut col_typ = vec![];
         let mut col_str = vec![];
         for col in col {
-            let i = &table
+            let (i, x) = &table
                 .columns
                 .iter()
-                .find_map(|(i, x)| (&x.name == col).then_some(i))
+                .find(|(_, x)| &x.name == col)
                 .ok_or_else(|| {
                     syn::Error::new_spanned(
                         col,
@@ -82,7 +92,7 @@ fn define_table(
             let tmp = format_ident!("_{table_ident}{i}");
 
             col_typ.push(tmp);
-            col_str.push(col.to_string());
+            col_str.push(x.sql_name.clone());
         }
 
         unique_typs.push(quote! {f.unique(&[#(#col_str),*])});
@@ -118,6 +128,7 @@ fn define_table(
 
     for (i, col) in &table.columns {
         let ident = &col.name;
+        let ident_str = &col.sql_name;
         let tmp = format_ident!("_{table_ident}{i}", span = col.typ.span());
 
         let mut unique_columns = table.uniques.iter().flat_map(|u| &u.columns);
@@ -129,9 +140,12 @@ fn define_table(
             update_columns_safe.push(quote! {::rust_query::private::Update<'t>});
             try_from_update.push(quote! {val.#ident});
         }
+        if col.index {
+            def_typs.push(quote!(f.index(&[#ident_str])));
+        }
         parts.push(quote! {::rust_query::FromExpr::from_expr(col.#ident())});
         generic.push(make_generic(ident));
-        col_str.push(ident.to_string());
+        col_str.push(col.sql_name.clone());
         col_ident.push(ident);
 
         if col.is_def {
@@ -254,6 +268,9 @@ fn define_table(
                     #(#unique_typs;)*
                 }
 
+                const COLUMNS: &'static [::rust_query::migration::ColumnMeta] = &[#(
+                    ::rust_query::migration::ColumnMeta::new::<#col_typ>(#col_str),
+                )*];
                 const ID: &'static str = "id";
                 const NAME: &'static str = #table_name;
 
@@ -261,6 +278,7 @@ fn define_table(
                 type UpdateOk<'t> = (#table_ident<#(#update_columns_safe),*>);
                 type Update<'t> = (#table_ident<#(#empty ::rust_query::private::Update<'t>),*>);
                 type Insert<'t> = (#table_ident<#(#empty ::rust_query::private::AsExpr<'t>),*>);
+                type Full<'t> = (#table_ident<#(#empty ::rust_query::private::Native<'t>),*>);
 
                 fn read<'t>(val: &Self::Insert<'t>, f: &::rust_query::private::Reader<'t, Self::Schema>) {
                     #(f.col(#col_str, &val.#col_ident);)*
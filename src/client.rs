@@ -2,7 +2,7 @@ use std::cell::Cell;
 
 use rusqlite::Connection;
 
-use crate::{Database, Transaction, TransactionMut};
+use crate::{Database, Transaction, TransactionMut, hooks::Hooks};
 
 /// The primary interface to the database.
 ///
@@ -14,6 +14,7 @@ use crate::{Database, Transaction, TransactionMut};
 pub struct LocalClient {
     _p: std::marker::PhantomData<*const ()>,
     pub(crate) conn: Option<Connection>,
+    pub(crate) hooks: Hooks,
 }
 
 impl LocalClient {
@@ -23,9 +24,8 @@ impl LocalClient {
     /// was created. This can happen for example by running another instance of your program with
     /// additional migrations.
     pub fn transaction<S>(&mut self, db: &Database<S>) -> Transaction<S> {
-        use r2d2::ManageConnection;
         // TODO: could check here if the existing connection is good to use.
-        let conn = self.conn.insert(db.manager.connect().unwrap());
+        let conn = self.connect(&db.manager);
         let txn = conn.transaction().unwrap();
         Transaction::new_checked(txn, db.schema_version)
     }
@@ -43,11 +43,10 @@ impl LocalClient {
     /// was created. This can happen for example by running another instance of your program with
     /// additional migrations.
     pub fn transaction_mut<S>(&mut self, db: &Database<S>) -> TransactionMut<S> {
-        use r2d2::ManageConnection;
         // TODO: could check here if the existing connection is good to use.
         // TODO: make sure that when reusing a connection, the foreign keys are checked (migration doesn't)
         // .pragma_update(None, "foreign_keys", "ON").unwrap();
-        let conn = self.conn.insert(db.manager.connect().unwrap());
+        let conn = self.connect(&db.manager);
         let txn = conn
             .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
             .unwrap();
@@ -62,10 +61,22 @@ thread_local! {
 }
 
 impl LocalClient {
+    /// Make a new connection that replaces the previous connection.
+    pub(crate) fn connect(
+        &mut self,
+        manager: &r2d2_sqlite::SqliteConnectionManager,
+    ) -> &mut Connection {
+        use r2d2::ManageConnection;
+        let conn = self.conn.insert(manager.connect().unwrap());
+        self.hooks.apply(conn);
+        conn
+    }
+
     fn new() -> Self {
         LocalClient {
             _p: std::marker::PhantomData,
             conn: None,
+            hooks: Hooks::default(),
         }
     }
 
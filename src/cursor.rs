@@ -0,0 +1,231 @@
+use std::{fmt::Display, marker::PhantomData, str::FromStr};
+
+use rusqlite::types::{FromSql, Value, ValueRef};
+use sea_query::{ExprTrait, Order, SimpleExpr};
+
+use crate::{
+    Expr, IntoExpr, IntoSelect, Select,
+    args::Query,
+    dummy_impl::{Cached, Cacher, SelectImpl},
+    value::{DynTyped, MyTyp, OrdTyp, SecretFromSql, Typed},
+};
+
+/// Types that can be used as key for [Query::into_vec_with_cursor].
+///
+/// This is every type that can be compared with `<` and `>` and the optional version of those types.
+#[diagnostic::on_unimplemented(
+    message = "Columns with type `{Self}` can not be used as cursor key"
+)]
+pub trait CursorKey: MyTyp {}
+
+impl<T: OrdTyp> CursorKey for T {}
+impl<T: OrdTyp> CursorKey for Option<T> {}
+
+/// Position in a query for keyset pagination, as returned by [Query::into_vec_with_cursor].
+///
+/// The cursor is not tied to a transaction and can be converted to and from a [String]
+/// with [Display] and [FromStr] to pass it to the next request.
+/// With the `serde` feature, the cursor also implements `Serialize` and `Deserialize` using the same string.
+pub struct Cursor<K> {
+    value: Value,
+    _p: PhantomData<fn() -> K>,
+}
+
+impl<K> Clone for Cursor<K> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<K> std::fmt::Debug for Cursor<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Cursor").field(&self.value).finish()
+    }
+}
+
+impl<K> PartialEq for Cursor<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<K> Display for Cursor<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            Value::Null => write!(f, "n"),
+            Value::Integer(val) => write!(f, "i{val}"),
+            Value::Real(val) => write!(f, "r{val}"),
+            Value::Text(val) => write!(f, "t{val}"),
+            Value::Blob(val) => {
+                write!(f, "b")?;
+                val.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+            }
+        }
+    }
+}
+
+/// The error returned when parsing a [Cursor] fails.
+#[derive(Debug)]
+pub struct InvalidCursor;
+
+impl Display for InvalidCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid cursor")
+    }
+}
+
+impl std::error::Error for InvalidCursor {}
+
+impl<K> FromStr for Cursor<K> {
+    type Err = InvalidCursor;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = match s.split_at_checked(1).ok_or(InvalidCursor)? {
+            ("n", "") => Value::Null,
+            ("i", val) => Value::Integer(val.parse().map_err(|_| InvalidCursor)?),
+            ("r", val) => Value::Real(val.parse().map_err(|_| InvalidCursor)?),
+            ("t", val) => Value::Text(val.to_owned()),
+            ("b", val) if val.len() % 2 == 0 && val.is_ascii() => Value::Blob(
+                (0..val.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&val[i..i + 2], 16).map_err(|_| InvalidCursor))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(InvalidCursor),
+        };
+        Ok(Self {
+            value,
+            _p: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K> serde::Serialize for Cursor<K> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K> serde::Deserialize<'de> for Cursor<K> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl<K> Cursor<K> {
+    fn to_sea_value(&self) -> sea_query::Value {
+        match self.value.clone() {
+            Value::Null => sea_query::Value::BigInt(None),
+            Value::Integer(val) => val.into(),
+            Value::Real(val) => val.into(),
+            Value::Text(val) => val.into(),
+            Value::Blob(val) => val.into(),
+        }
+    }
+}
+
+impl SecretFromSql<'_> for Value {
+    fn from_sql(value: ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        FromSql::column_result(value)
+    }
+}
+
+struct RawImpl<T> {
+    expr: DynTyped<T>,
+}
+
+impl<'transaction, T: 'static> SelectImpl<'transaction> for RawImpl<T> {
+    type Out = Value;
+    type Prepared = Cached<Value>;
+
+    fn prepare(self, cacher: &mut Cacher) -> Self::Prepared {
+        Cached::new(cacher.cache_erased(self.expr.erase()))
+    }
+}
+
+impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
+    /// Retrieve one page of results ordered by `key`, together with a [Cursor] for the next page.
+    ///
+    /// At most `page_size` results are returned sorted by `key`,
+    /// replacing any [Query::limit], [Query::offset] and [Query::order_by].
+    /// The cursor is [None] when this is the last page.
+    /// Use [Query::after_cursor] with the same `key` to retrieve the next page.
+    ///
+    /// The `key` should be unique for each row, otherwise rows with the same key can be skipped.
+    /// A good choice is usually the row reference itself.
+    /// Rows where an optional `key` is [None] are returned first.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let (page, cursor) = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec_with_cursor(user.name(), user.name(), 2)
+    /// });
+    /// assert_eq!(page, ["Alice", "Bob"]);
+    ///
+    /// let cursor = cursor.unwrap().to_string().parse().unwrap();
+    /// let (page, cursor) = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.after_cursor(user.name(), &cursor);
+    ///     rows.into_vec_with_cursor(user.name(), user.name(), 2)
+    /// });
+    /// assert_eq!(page, ["Charlie"]);
+    /// assert!(cursor.is_none());
+    /// ```
+    pub fn into_vec_with_cursor<O, K: CursorKey>(
+        &self,
+        key: impl IntoExpr<'inner, S, Typ = K>,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+        page_size: u64,
+    ) -> (Vec<O>, Option<Cursor<K>>) {
+        let key = key.into_expr().inner;
+        let field = self.ast.cache([key.clone().erase()])[0];
+        let raw = Select::new(RawImpl { expr: key });
+
+        let mut out = self.collect_private((select, raw), |select| {
+            // the cursor only works when the results are sorted by `key` alone
+            select.clear_order_by().reset_offset();
+            select.order_by(field, Order::Asc);
+            // retrieve one extra row to find out if there is a next page
+            select.limit(page_size + 1);
+        });
+
+        let mut cursor = None;
+        if out.len() as u64 > page_size {
+            out.truncate(page_size as usize);
+            cursor = out.last().map(|(_, value)| Cursor {
+                value: value.clone(),
+                _p: PhantomData,
+            });
+        }
+        (out.into_iter().map(|(val, _)| val).collect(), cursor)
+    }
+
+    /// Only include rows that come after the `cursor` when ordered by `key`.
+    ///
+    /// Please refer to [Query::into_vec_with_cursor].
+    pub fn after_cursor<K: CursorKey>(
+        &mut self,
+        key: impl IntoExpr<'inner, S, Typ = K>,
+        cursor: &Cursor<K>,
+    ) {
+        let key = key.into_expr().inner;
+        // sqlite sorts `NULL` before all other values
+        let is_null = cursor.value == Value::Null;
+        let value = SimpleExpr::Value(cursor.to_sea_value());
+        self.filter(Expr::<S, bool>::adhoc(move |b| match is_null {
+            true => key.build_expr(b).is_not_null(),
+            false => key.build_expr(b).gt(value.clone()),
+        }));
+    }
+}
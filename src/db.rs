@@ -1,4 +1,9 @@
-use std::{fmt::Debug, marker::PhantomData, ops::Deref};
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+};
 
 use ref_cast::RefCast;
 use sea_query::{Alias, SimpleExpr};
@@ -89,6 +94,22 @@ impl<T: Table> Typed for Join<T> {
 ///
 /// The appropriate solution is to use [crate::args::Aggregate::filter_on] to bring [TableRow]
 /// columns into the [crate::aggregate] inner scope.
+///
+/// [TableRow] implements [Eq] and [Hash], which makes it possible to deduplicate rows with a [HashSet](std::collections::HashSet).
+///
+/// ```
+/// # use std::collections::HashSet;
+/// # use rust_query::{Table, private::doctest::*};
+/// # let mut client = get_client();
+/// # let mut txn = get_txn(&mut client);
+/// txn.insert(User { name: "Bob" }).unwrap();
+/// let users: HashSet<_> = txn.query(|rows| {
+///     let a = User::join(rows);
+///     let _b = User::join(rows);
+///     rows.into_vec(a)
+/// }).into_iter().collect();
+/// assert_eq!(users.len(), 2);
+/// ```
 pub struct TableRow<'t, T> {
     pub(crate) _p: PhantomData<&'t ()>,
     pub(crate) _local: PhantomData<LocalClient>,
@@ -132,6 +153,12 @@ impl<'t, T> PartialEq for TableRow<'t, T> {
     }
 }
 
+impl<T> Hash for TableRow<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.idx.hash(state);
+    }
+}
+
 impl<T> Debug for TableRow<'_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "db_{}", self.inner.idx)
@@ -180,6 +207,95 @@ impl<'t, S, T: Table> IntoExpr<'t, S> for TableRow<'t, T> {
     }
 }
 
+impl<'t, T> TableRow<'t, T> {
+    /// Convert the row reference into an [OwnedTableRow] that is not tied to the transaction.
+    pub fn into_owned(self) -> OwnedTableRow<T> {
+        OwnedTableRow {
+            _p: PhantomData,
+            idx: self.inner.idx,
+        }
+    }
+}
+
+/// Row reference that is not tied to a transaction.
+///
+/// This is created with [TableRow::into_owned] and can be used in later transactions.
+/// Because the row might have been deleted in the mean time, using it as an expression
+/// results in an [Option] of the row.
+///
+/// ```
+/// # use rust_query::{IntoExpr, LocalClient, OwnedTableRow, migration::Config, optional};
+/// # use rust_query::private::doctest::*;
+/// let mut client = LocalClient::try_new().unwrap();
+/// let db = client
+///     .migrator(Config::open_in_memory())
+///     .unwrap()
+///     .finish()
+///     .unwrap();
+/// let mut txn = client.transaction_mut(&db);
+/// let alice: OwnedTableRow<User> = txn.insert(User { name: "Alice" }).unwrap().into_owned();
+/// txn.commit();
+///
+/// let mut txn = client.transaction_mut(&db);
+/// let name = txn.query_one(optional(|row| {
+///     let user = row.and(alice);
+///     row.then(user.name())
+/// }));
+/// assert_eq!(name.as_deref(), Some("Alice"));
+///
+/// let row = txn.query_one(alice.into_expr()).unwrap();
+/// let mut txn = txn.downgrade();
+/// txn.delete(row).unwrap();
+/// txn.commit();
+///
+/// let txn = client.transaction(&db);
+/// assert_eq!(txn.query_one(alice.into_expr()), None);
+/// ```
+pub struct OwnedTableRow<T> {
+    _p: PhantomData<fn() -> T>,
+    idx: i64,
+}
+
+impl<T> From<TableRow<'_, T>> for OwnedTableRow<T> {
+    fn from(value: TableRow<'_, T>) -> Self {
+        value.into_owned()
+    }
+}
+
+impl<T> Debug for OwnedTableRow<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "db_{}", self.idx)
+    }
+}
+
+impl<T> Clone for OwnedTableRow<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for OwnedTableRow<T> {}
+
+impl<T> PartialEq for OwnedTableRow<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.idx == other.idx
+    }
+}
+impl<T> Eq for OwnedTableRow<T> {}
+
+impl<T> Hash for OwnedTableRow<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.idx.hash(state);
+    }
+}
+
+impl<'t, S, T: Table> IntoExpr<'t, S> for OwnedTableRow<T> {
+    type Typ = Option<T>;
+    fn into_expr(self) -> Expr<'t, S, Self::Typ> {
+        let idx = self.idx;
+        Expr::adhoc(move |b| b.get_unique::<T>(vec![(T::ID, sea_query::Expr::val(idx).into())]))
+    }
+}
+
 /// This makes it possible to use TableRow as a parameter in
 /// rusqlite queries and statements.
 impl<T> rusqlite::ToSql for TableRow<'_, T> {
@@ -212,6 +328,7 @@ mod tests {
         type UpdateOk<'t> = ();
         type Update<'t> = ();
         type Insert<'t> = ();
+        type Full<'t> = ();
 
         fn read<'t>(val: &Self::Insert<'t>, f: &Reader<'t, Self::Schema>) {
             todo!()
@@ -235,6 +352,7 @@ mod tests {
             todo!()
         }
 
+        const COLUMNS: &'static [crate::migrate::ColumnMeta] = &[];
         const ID: &'static str = "";
         const NAME: &'static str = "";
     }
@@ -26,6 +26,15 @@ pub struct Cached<T> {
     _p: PhantomData<T>,
 }
 
+impl<T> Cached<T> {
+    pub(crate) fn new(idx: usize) -> Self {
+        Self {
+            idx,
+            _p: PhantomData,
+        }
+    }
+}
+
 impl<T> Clone for Cached<T> {
     fn clone(&self) -> Self {
         *self
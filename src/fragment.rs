@@ -0,0 +1,71 @@
+use std::rc::Rc;
+
+use crate::{Expr, rows::Rows};
+
+/// A reusable part of a query that can be stored and passed around.
+///
+/// Queries are normally written as closures that can not be named.
+/// [QueryFragment] wraps such a closure, so that it can be returned from functions
+/// and combined with filters before being used in a query with [QueryFragment::join].
+///
+/// ```
+/// # use rust_query::{QueryFragment, Table, private::doctest::*};
+/// # let mut client = get_client();
+/// # let mut txn = get_txn(&mut client);
+/// fn users() -> QueryFragment<Empty, User> {
+///     QueryFragment::new(|rows| User::join(rows))
+/// }
+/// txn.insert(User { name: "Bob" }).unwrap();
+///
+/// let bob_only = users().filter(|user| user.name().eq("Bob"));
+/// let names = txn.query(|rows| {
+///     let user = bob_only.join(rows);
+///     rows.into_vec(user.name())
+/// });
+/// assert_eq!(names, ["Bob"]);
+/// ```
+pub struct QueryFragment<S, T> {
+    inner: Rc<DynFragment<S, T>>,
+}
+
+type DynFragment<S, T> = dyn for<'inner> Fn(&mut Rows<'inner, S>) -> Expr<'inner, S, T>;
+
+impl<S, T> Clone for QueryFragment<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S: 'static, T: 'static> QueryFragment<S, T> {
+    /// Create a new [QueryFragment] from a closure.
+    ///
+    /// The closure can join tables and add filters to [Rows], it returns the value of the fragment.
+    pub fn new(
+        f: impl 'static + for<'inner> Fn(&mut Rows<'inner, S>) -> Expr<'inner, S, T>,
+    ) -> Self {
+        Self { inner: Rc::new(f) }
+    }
+
+    /// Add a filter to the [QueryFragment].
+    ///
+    /// The filter is applied to every query that uses the returned fragment.
+    pub fn filter(
+        self,
+        f: impl 'static + for<'inner> Fn(&Expr<'inner, S, T>) -> Expr<'inner, S, bool>,
+    ) -> Self {
+        Self::new(move |rows| {
+            let val = (self.inner)(rows);
+            rows.filter(f(&val));
+            val
+        })
+    }
+
+    /// Use the [QueryFragment] in a query.
+    ///
+    /// This applies all joins and filters of the fragment to `rows` and returns the value.
+    pub fn join<'inner>(&self, rows: &mut Rows<'inner, S>) -> Expr<'inner, S, T> {
+        (self.inner)(rows)
+    }
+}
@@ -0,0 +1,65 @@
+//! Scalar functions that are registered on every connection.
+
+use std::sync::Arc;
+
+use rusqlite::{Connection, functions::FunctionFlags, types::ValueRef};
+
+/// Sqlite only has math functions when it is compiled with `SQLITE_ENABLE_MATH_FUNCTIONS`.
+/// If they are missing, we register our own implementation.
+pub(crate) fn register_math(conn: &Connection) -> rusqlite::Result<()> {
+    if conn.prepare("SELECT pow(2.0, 2.0)").is_ok() {
+        return Ok(());
+    }
+    let flags = FunctionFlags::SQLITE_UTF8
+        | FunctionFlags::SQLITE_DETERMINISTIC
+        | FunctionFlags::SQLITE_INNOCUOUS;
+    // sqlite returns NULL instead of NaN
+    let not_nan = |x: f64| (!x.is_nan()).then_some(x);
+
+    conn.create_scalar_function("pow", 2, flags, move |ctx| {
+        Ok(not_nan(ctx.get::<f64>(0)?.powf(ctx.get(1)?)))
+    })?;
+    conn.create_scalar_function("sqrt", 1, flags, move |ctx| {
+        Ok(not_nan(ctx.get::<f64>(0)?.sqrt()))
+    })?;
+    conn.create_scalar_function("log", 2, flags, move |ctx| {
+        let (base, x) = (ctx.get::<f64>(0)?, ctx.get::<f64>(1)?);
+        // sqlite returns NULL outside of the domain instead of infinity
+        if x <= 0. || base <= 0. || base == 1. {
+            return Ok(None);
+        }
+        Ok(not_nan(x.log(base)))
+    })?;
+    conn.create_scalar_function("exp", 1, flags, move |ctx| {
+        Ok(not_nan(ctx.get::<f64>(0)?.exp()))
+    })?;
+    Ok(())
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Register the `regexp` function that sqlite uses for the `REGEXP` operator.
+pub(crate) fn register_regexp<R, E>(
+    conn: &Connection,
+    compile: Arc<impl Fn(&str) -> Result<R, E> + Send + Sync + 'static>,
+) -> rusqlite::Result<()>
+where
+    R: Fn(&str) -> bool + Send + Sync + 'static,
+    E: Into<BoxError>,
+{
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("regexp", 2, flags, move |ctx| {
+        // sqlite keeps the matcher as long as the pattern argument does not change
+        let matcher: Arc<R> = ctx.get_or_create_aux(0, |pattern| -> Result<_, BoxError> {
+            compile(pattern.as_str()?).map_err(Into::into)
+        })?;
+        let text = ctx.get_raw(1);
+        if text == ValueRef::Null {
+            return Ok(None);
+        }
+        let text = text
+            .as_str()
+            .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?;
+        Ok(Some(matcher(text)))
+    })
+}
@@ -2,9 +2,13 @@
 //! The layout is hashable and the hashes are independent
 //! of the column ordering and some other stuff.
 
-use std::{marker::PhantomData, ops::Deref};
+use std::{
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+};
 
-use sea_query::TableCreateStatement;
+use sea_query::{IndexCreateStatement, TableCreateStatement};
 
 use crate::value::{EqTyp, MyTyp};
 
@@ -26,6 +30,15 @@ impl ColumnType {
             ColumnType::Blob => T::Blob,
         }
     }
+
+    pub const fn sql_name(&self) -> &'static str {
+        match self {
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Float => "REAL",
+            ColumnType::String => "TEXT",
+            ColumnType::Blob => "BLOB",
+        }
+    }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -42,9 +55,38 @@ pub struct Unique {
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Index {
+    pub columns: MyVec<String>,
+}
+
+impl Index {
+    fn name(&self, table_name: &str) -> String {
+        let mut name = table_name.to_owned();
+        for col in &*self.columns {
+            name.push('_');
+            name.push_str(col);
+        }
+        name.push_str("_index");
+        name
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct Table {
     pub columns: MyVec<Column>,
     pub uniques: MyVec<Unique>,
+    pub indexes: MyVec<Index>,
+}
+
+impl Hash for Table {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.columns.hash(state);
+        self.uniques.hash(state);
+        // tables without indexes keep the same hash as before indexes were supported
+        if !self.indexes.is_empty() {
+            self.indexes.hash(state);
+        }
+    }
 }
 
 /// Special [Vec] wrapper with a hash that is independent of the item order
@@ -106,6 +148,30 @@ impl Table {
         }
         create
     }
+
+    pub fn create_indexes(&self, table_name: &str) -> Vec<IndexCreateStatement> {
+        use sea_query::*;
+        self.indexes
+            .iter()
+            .map(|index| {
+                let mut create = Index::create();
+                for col in &*index.columns {
+                    create.col(Alias::new(col));
+                }
+                let name = index.name(table_name);
+                create.name(name).table(Alias::new(table_name)).take()
+            })
+            .collect()
+    }
+
+    /// The names of all indexes that sqlite has for this table.
+    pub fn index_names(&self, table_name: &str) -> Vec<String> {
+        // unique constraints are part of the table definition, so sqlite names them
+        let uniques =
+            (1..=self.uniques.len()).map(|i| format!("sqlite_autoindex_{table_name}_{i}"));
+        let indexes = self.indexes.iter().map(|index| index.name(table_name));
+        uniques.chain(indexes).collect()
+    }
 }
 
 #[derive(Debug, Hash, Default, PartialEq, Eq)]
@@ -195,11 +261,19 @@ impl<S> TypBuilder<S> {
         self.ast.uniques.insert(unique);
     }
 
+    pub fn index(&mut self, cols: &[&'static str]) {
+        let mut index = Index::default();
+        for &col in cols {
+            index.columns.insert(col.to_owned());
+        }
+        self.ast.indexes.insert(index);
+    }
+
     pub fn check_unique_compatible<T: EqTyp>(&mut self) {}
 }
 
-struct Null;
-struct NotNull;
+pub(crate) struct Null;
+pub(crate) struct NotNull;
 
 // TODO: maybe remove this trait?
 // currently this prevents storing booleans and nested `Option`.
@@ -207,7 +281,7 @@ struct NotNull;
     message = "Can not use `{Self}` as a column type in schema `{S}`",
     note = "Table names can be used as schema column types as long as they are not #[no_reference]"
 )]
-trait SchemaType<S>: MyTyp {
+pub(crate) trait SchemaType<S>: MyTyp {
     type N;
 }
 
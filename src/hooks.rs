@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use rusqlite::{
+    Connection,
+    hooks::{AuthContext, Authorization},
+};
+
+use crate::LocalClient;
+
+/// An operation that sqlite wants to perform, this is the argument of [LocalClient::with_authorizer].
+#[derive(Clone, Copy, Debug)]
+pub struct AuthAction<'a> {
+    /// The action and its arguments.
+    pub action: rusqlite::hooks::AuthAction<'a>,
+    /// The name of the database (e.g. "main" or "temp"), if applicable.
+    pub database: Option<&'a str>,
+}
+
+impl<'a> AuthAction<'a> {
+    /// The action code as defined by sqlite, e.g. `SQLITE_READ` is 20.
+    pub fn code(&self) -> i32 {
+        use rusqlite::{ffi, hooks::AuthAction as A};
+        match self.action {
+            A::Unknown { code, .. } => code,
+            A::CreateIndex { .. } => ffi::SQLITE_CREATE_INDEX,
+            A::CreateTable { .. } => ffi::SQLITE_CREATE_TABLE,
+            A::CreateTempIndex { .. } => ffi::SQLITE_CREATE_TEMP_INDEX,
+            A::CreateTempTable { .. } => ffi::SQLITE_CREATE_TEMP_TABLE,
+            A::CreateTempTrigger { .. } => ffi::SQLITE_CREATE_TEMP_TRIGGER,
+            A::CreateTempView { .. } => ffi::SQLITE_CREATE_TEMP_VIEW,
+            A::CreateTrigger { .. } => ffi::SQLITE_CREATE_TRIGGER,
+            A::CreateView { .. } => ffi::SQLITE_CREATE_VIEW,
+            A::Delete { .. } => ffi::SQLITE_DELETE,
+            A::DropIndex { .. } => ffi::SQLITE_DROP_INDEX,
+            A::DropTable { .. } => ffi::SQLITE_DROP_TABLE,
+            A::DropTempIndex { .. } => ffi::SQLITE_DROP_TEMP_INDEX,
+            A::DropTempTable { .. } => ffi::SQLITE_DROP_TEMP_TABLE,
+            A::DropTempTrigger { .. } => ffi::SQLITE_DROP_TEMP_TRIGGER,
+            A::DropTempView { .. } => ffi::SQLITE_DROP_TEMP_VIEW,
+            A::DropTrigger { .. } => ffi::SQLITE_DROP_TRIGGER,
+            A::DropView { .. } => ffi::SQLITE_DROP_VIEW,
+            A::Insert { .. } => ffi::SQLITE_INSERT,
+            A::Pragma { .. } => ffi::SQLITE_PRAGMA,
+            A::Read { .. } => ffi::SQLITE_READ,
+            A::Select => ffi::SQLITE_SELECT,
+            A::Transaction { .. } => ffi::SQLITE_TRANSACTION,
+            A::Update { .. } => ffi::SQLITE_UPDATE,
+            A::Attach { .. } => ffi::SQLITE_ATTACH,
+            A::Detach { .. } => ffi::SQLITE_DETACH,
+            A::AlterTable { .. } => ffi::SQLITE_ALTER_TABLE,
+            A::Reindex { .. } => ffi::SQLITE_REINDEX,
+            A::Analyze { .. } => ffi::SQLITE_ANALYZE,
+            A::CreateVtable { .. } => ffi::SQLITE_CREATE_VTABLE,
+            A::DropVtable { .. } => ffi::SQLITE_DROP_VTABLE,
+            A::Function { .. } => ffi::SQLITE_FUNCTION,
+            A::Savepoint { .. } => ffi::SQLITE_SAVEPOINT,
+            A::Recursive => ffi::SQLITE_RECURSIVE,
+            _ => ffi::SQLITE_OK,
+        }
+    }
+
+    /// The name of the table that the action is about, if applicable.
+    pub fn table(&self) -> Option<&'a str> {
+        use rusqlite::hooks::AuthAction as A;
+        match self.action {
+            A::CreateIndex { table_name, .. }
+            | A::CreateTable { table_name }
+            | A::CreateTempIndex { table_name, .. }
+            | A::CreateTempTable { table_name }
+            | A::CreateTempTrigger { table_name, .. }
+            | A::CreateTrigger { table_name, .. }
+            | A::Delete { table_name }
+            | A::DropIndex { table_name, .. }
+            | A::DropTable { table_name }
+            | A::DropTempIndex { table_name, .. }
+            | A::DropTempTable { table_name }
+            | A::DropTempTrigger { table_name, .. }
+            | A::DropTrigger { table_name, .. }
+            | A::Insert { table_name }
+            | A::Read { table_name, .. }
+            | A::Update { table_name, .. }
+            | A::AlterTable { table_name, .. }
+            | A::Analyze { table_name }
+            | A::CreateVtable { table_name, .. }
+            | A::DropVtable { table_name, .. } => Some(table_name),
+            _ => None,
+        }
+    }
+
+    /// The name of the column that is read or updated, if applicable.
+    pub fn column(&self) -> Option<&'a str> {
+        use rusqlite::hooks::AuthAction as A;
+        match self.action {
+            A::Read { column_name, .. } | A::Update { column_name, .. } => Some(column_name),
+            _ => None,
+        }
+    }
+}
+
+/// The result of the callback in [LocalClient::with_authorizer].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthResult {
+    /// Allow the action.
+    Allow,
+    /// Disallow the action, but continue without an error.
+    /// Columns that are read will be `NULL` and rows that are modified will be skipped.
+    Ignore,
+    /// Disallow the action and fail with an error.
+    Deny,
+}
+
+type Authorizer = Arc<dyn Fn(AuthAction) -> AuthResult + Send + Sync>;
+type ProgressHandler = Arc<dyn Fn() -> bool + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    authorizer: Option<Authorizer>,
+    progress_handler: Option<(i32, ProgressHandler)>,
+}
+
+impl Hooks {
+    pub(crate) fn apply(&self, conn: &Connection) {
+        if let Some(authorizer) = self.authorizer.clone() {
+            conn.authorizer(Some(move |ctx: AuthContext<'_>| {
+                let action = AuthAction {
+                    action: ctx.action,
+                    database: ctx.database_name,
+                };
+                match authorizer(action) {
+                    AuthResult::Allow => Authorization::Allow,
+                    AuthResult::Ignore => Authorization::Ignore,
+                    AuthResult::Deny => Authorization::Deny,
+                }
+            }));
+        }
+        if let Some((steps, handler)) = self.progress_handler.clone() {
+            // sqlite interrupts the query when the handler returns `true`
+            conn.progress_handler(steps, Some(move || !handler()));
+        }
+    }
+}
+
+impl LocalClient {
+    /// Register a callback that can allow or deny every operation that sqlite performs.
+    ///
+    /// The callback is called when statements are prepared, it is used for all connections
+    /// that are made by this [LocalClient], including the connection used for migrations.
+    /// Note that the internal queries of this library also have to be allowed.
+    ///
+    /// ```
+    /// # use rust_query::{AuthResult, LocalClient, Table, private::doctest::*};
+    /// let mut client = LocalClient::try_new().unwrap().with_authorizer(|action| {
+    ///     if action.table() == Some("secret") {
+    ///         AuthResult::Deny
+    ///     } else {
+    ///         AuthResult::Allow
+    ///     }
+    /// });
+    /// let txn = get_txn(&mut client);
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Alice"]);
+    /// ```
+    pub fn with_authorizer(
+        mut self,
+        cb: impl Fn(AuthAction) -> AuthResult + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.authorizer = Some(Arc::new(cb));
+        self
+    }
+
+    /// Register a callback that is called every `steps` sqlite virtual machine instructions.
+    ///
+    /// If the callback returns `false`, then the current query is cancelled.
+    /// Cancelled queries result in a panic, just like other sqlite errors.
+    /// This can be used to implement timeouts or cancellation of long running queries,
+    /// together with [std::panic::catch_unwind].
+    ///
+    /// ```
+    /// # use std::sync::atomic::{AtomicU64, Ordering};
+    /// # use rust_query::{LocalClient, Table, private::doctest::*};
+    /// static STEPS: AtomicU64 = AtomicU64::new(0);
+    /// let mut client = LocalClient::try_new().unwrap().with_progress_handler(1, || {
+    ///     STEPS.fetch_add(1, Ordering::Relaxed);
+    ///     true
+    /// });
+    /// let txn = get_txn(&mut client);
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Alice"]);
+    /// assert!(STEPS.load(Ordering::Relaxed) > 0);
+    /// ```
+    pub fn with_progress_handler(
+        mut self,
+        steps: i32,
+        cb: impl Fn() -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.progress_handler = Some((steps, Arc::new(cb)));
+        self
+    }
+}
@@ -9,30 +9,42 @@ extern crate static_assertions;
 mod alias;
 mod ast;
 mod client;
+mod cursor;
 mod db;
 mod dummy_impl;
+mod fragment;
+mod functions;
 mod hash;
+mod hooks;
 mod migrate;
 mod mymap;
+mod plan;
 mod query;
 mod ref_cast_impl;
 mod rows;
 mod schema_pragma;
 mod transaction;
 mod value;
+mod window;
 mod writable;
 
 pub use client::LocalClient;
-pub use db::TableRow;
+pub use cursor::{Cursor, InvalidCursor};
+pub use db::{OwnedTableRow, TableRow};
 pub use dummy_impl::{IntoSelect, IntoSelectExt, Select};
+pub use fragment::QueryFragment;
 use hash::TypBuilder;
+pub use hooks::{AuthAction, AuthResult};
+pub use plan::QueryPlan;
 use private::Reader;
+pub use query::{SortDir, TooManyRows};
 use ref_cast::RefCast;
 use rows::Rows;
 pub use rust_query_macros::{FromExpr, Select};
-pub use transaction::{Database, Transaction, TransactionMut, TransactionWeak};
+pub use transaction::{BulkInserter, Database, Transaction, TransactionMut, TransactionWeak};
 use value::MyTyp;
 pub use value::aggregate::aggregate;
+pub use value::computed::ComputedColumn;
 pub use value::trivial::FromExpr;
 pub use value::{Expr, IntoExpr, UnixEpoch, optional::optional};
 pub use writable::Update;
@@ -47,13 +59,23 @@ pub mod args {
     pub use crate::value::optional::Optional;
 }
 
+/// Functions for timestamps that are stored as unix seconds, like [UnixEpoch].
+pub mod date {
+    pub use crate::value::date::{
+        DateUnit, date_diff, date_trunc, day_of_week, month, strftime, year,
+    };
+}
+
 /// Types to declare schemas and migrations.
 ///
 /// A good starting point is too look at [crate::migration::schema].
 pub mod migration {
     #[cfg(feature = "dev")]
     pub use crate::hash::dev::hash_schema;
-    pub use crate::migrate::{Config, Migrated, Migrator, TransactionMigrate};
+    pub use crate::migrate::{
+        ColumnMeta, Config, Migrated, Migrator, TableMeta, TransactionMigrate,
+    };
+    pub use crate::schema_pragma::scaffold_schema;
     pub use rust_query_macros::schema;
 }
 
@@ -170,6 +192,11 @@ pub trait Table: Sized + 'static {
     /// The type of error when a delete fails due to a foreign key constraint.
     type Referer;
 
+    /// The type of a full row of this table, as returned by [Transaction::get].
+    ///
+    /// This is the table struct with every column retrieved.
+    type Full<'t>: FromExpr<'t, Self::Schema, Self>;
+
     #[doc(hidden)]
     type Insert<'t>;
 
@@ -202,6 +229,9 @@ pub trait Table: Sized + 'static {
     #[doc(hidden)]
     fn typs(f: &mut TypBuilder<Self::Schema>);
 
+    #[doc(hidden)]
+    const COLUMNS: &'static [migrate::ColumnMeta];
+
     #[doc(hidden)]
     const ID: &'static str;
     #[doc(hidden)]
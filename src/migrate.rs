@@ -5,19 +5,20 @@ use std::{
     ops::{Deref, Not},
     path::Path,
     rc::Rc,
-    sync::atomic::AtomicBool,
+    sync::{Arc, atomic::AtomicBool},
 };
 
 use rusqlite::{Connection, config::DbConfig};
 use sea_query::{Alias, ColumnDef, IntoTableRef, SqliteQueryBuilder, TableDropStatement};
 
 use crate::{
-    FromExpr, Table, TableRow, Transaction,
+    FromExpr, Table, TableRow, Transaction, TransactionMut,
     alias::{Scope, TmpTable},
     client::LocalClient,
-    hash,
+    functions, hash,
     schema_pragma::read_schema,
     transaction::{Database, try_insert_private},
+    value::MyTyp,
 };
 
 pub struct TableTypBuilder<S> {
@@ -44,9 +45,68 @@ impl<S> TableTypBuilder<S> {
 
 pub trait Schema: Sized + 'static {
     const VERSION: i64;
+    const TABLES: &'static [TableMeta];
     fn typs(b: &mut TableTypBuilder<Self>);
 }
 
+/// Description of a table in a schema, as returned by the generated `tables` function of each schema.
+///
+/// ```
+/// # use rust_query::private::doctest::*;
+/// let tables = Empty::tables();
+/// assert_eq!(tables[0].name, "user");
+/// assert_eq!(tables[0].columns[0].name, "name");
+/// assert_eq!(tables[0].columns[0].sql_type, "TEXT");
+/// ```
+///
+/// The hash of a schema can not be computed at compile time.
+/// Use [crate::migration::hash_schema] for that instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableMeta {
+    /// The name of the table in sqlite.
+    pub name: &'static str,
+    /// All columns of the table, excluding the `id` column.
+    pub columns: &'static [ColumnMeta],
+}
+
+impl TableMeta {
+    #[doc(hidden)]
+    pub const fn new<T: Table>() -> Self {
+        Self {
+            name: T::NAME,
+            columns: T::COLUMNS,
+        }
+    }
+}
+
+/// Description of a column in a table, see [TableMeta].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnMeta {
+    /// The name of the column in sqlite.
+    pub name: &'static str,
+    /// The sqlite type of the column, one of `INTEGER`, `REAL`, `TEXT` or `BLOB`.
+    pub sql_type: &'static str,
+    /// Whether the column can be `NULL`.
+    pub nullable: bool,
+    /// The name of the table that this column references, if it is a foreign key.
+    pub references: Option<&'static str>,
+}
+
+impl ColumnMeta {
+    #[doc(hidden)]
+    pub const fn new<T: MyTyp>(name: &'static str) -> Self {
+        Self {
+            name,
+            sql_type: T::TYP.sql_name(),
+            nullable: T::NULLABLE,
+            references: match T::FK {
+                Some((table, _)) => Some(table),
+                None => None,
+            },
+        }
+    }
+}
+
 pub trait Migration<'t> {
     type FromSchema: 'static;
     type From: Table<Schema = Self::FromSchema>;
@@ -215,6 +275,13 @@ fn new_table_inner(conn: &Connection, table: &crate::hash::Table, alias: impl In
     conn.execute(&sql, []).unwrap();
 }
 
+fn new_indexes(conn: &Connection, table: &crate::hash::Table, table_name: &str) {
+    for index in table.create_indexes(table_name) {
+        let sql = index.to_string(SqliteQueryBuilder);
+        conn.execute(&sql, []).unwrap();
+    }
+}
+
 pub trait SchemaMigration<'a> {
     type From: Schema;
     type To: Schema;
@@ -238,9 +305,13 @@ pub trait SchemaMigration<'a> {
 /// The database will not lose transactions due to application crashes, but it might due to system crashes or power loss.
 pub struct Config {
     manager: r2d2_sqlite::SqliteConnectionManager,
+    connect: Arc<ConnectFn>,
     init: Box<dyn FnOnce(&rusqlite::Transaction)>,
+    schema_sql: Option<String>,
 }
 
+type ConnectFn = dyn Fn(&mut Connection) -> rusqlite::Result<()> + Send + Sync;
+
 static ALLOWED: AtomicBool = AtomicBool::new(true);
 
 impl Config {
@@ -263,22 +334,128 @@ impl Config {
 
     fn open_internal(manager: r2d2_sqlite::SqliteConnectionManager) -> Self {
         assert!(ALLOWED.swap(false, std::sync::atomic::Ordering::Relaxed));
-        let manager = manager.with_init(|inner| {
-            inner.pragma_update(None, "journal_mode", "WAL")?;
-            inner.pragma_update(None, "synchronous", "NORMAL")?;
-            inner.pragma_update(None, "foreign_keys", "ON")?;
-            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DDL, false)?;
-            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DML, false)?;
-            inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DEFENSIVE, true)?;
-            Ok(())
-        });
+        let manager = manager.with_init(init_connection);
 
         Self {
             manager,
+            connect: Arc::new(init_connection),
             init: Box::new(|_| {}),
+            schema_sql: None,
         }
     }
 
+    /// Run `f` for every new connection, after all previous connection settings are applied.
+    fn on_connect(
+        mut self,
+        f: impl Fn(&mut Connection) -> rusqlite::Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        let prev = self.connect;
+        self.connect = Arc::new(move |conn| {
+            prev(conn)?;
+            f(conn)
+        });
+        let connect = self.connect.clone();
+        self.manager = self.manager.with_init(move |conn| connect(conn));
+        self
+    }
+
+    /// Create the tables with the sql from a file if the database was just created.
+    ///
+    /// This makes it possible to keep the schema sql in a separate file, for example to review it.
+    /// The file is read immediately, but it is only executed when the database does not exist yet.
+    /// The resulting schema is checked against the rust schema like any other database,
+    /// so a mismatch will result in a panic when creating the [crate::Database].
+    ///
+    /// ```
+    /// # use rust_query::migration::Config;
+    /// # use rust_query::private::doctest::*;
+    /// let path = std::env::temp_dir().join("rust_query_schema_file_doctest.sql");
+    /// std::fs::write(&path, r#"
+    ///     CREATE TABLE "user" (
+    ///         "name" text NOT NULL,
+    ///         "id" integer PRIMARY KEY,
+    ///         UNIQUE ("name")
+    ///     ) STRICT;
+    /// "#).unwrap();
+    ///
+    /// let config = Config::open_in_memory().schema_file(&path).unwrap();
+    /// let mut client = get_client();
+    /// let db = client.migrator::<Empty>(config).unwrap().finish().unwrap();
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn schema_file(mut self, p: impl AsRef<Path>) -> std::io::Result<Self> {
+        self.schema_sql = Some(std::fs::read_to_string(p)?);
+        Ok(self)
+    }
+
+    /// Limit the size of the write-ahead log to approximately `bytes`.
+    ///
+    /// Sqlite will automatically checkpoint the write-ahead log when it grows larger than `bytes`
+    /// (using the `wal_autocheckpoint` pragma) and truncate it back to at most `bytes`
+    /// after every checkpoint (using the `journal_size_limit` pragma).
+    /// The limit is applied to every connection that is made to the database.
+    ///
+    /// ```
+    /// # use rust_query::migration::Config;
+    /// # use rust_query::private::doctest::*;
+    /// let config = Config::open_in_memory().wal_size_limit(1 << 20);
+    /// let mut client = get_client();
+    /// let db = client.migrator::<Empty>(config).unwrap().finish().unwrap();
+    /// let txn = client.transaction(&db);
+    /// let limit = txn.query_raw("PRAGMA journal_size_limit", (), |row| row.get::<_, i64>(0));
+    /// assert_eq!(limit.unwrap(), [1 << 20]);
+    /// ```
+    pub fn wal_size_limit(self, bytes: u64) -> Self {
+        let bytes = bytes.min(i64::MAX as u64) as i64;
+        self.on_connect(move |inner| {
+            let page_size: i64 = inner.pragma_query_value(None, "page_size", |row| row.get(0))?;
+            inner.pragma_update(None, "wal_autocheckpoint", (bytes / page_size).max(1))?;
+            inner.pragma_update(None, "journal_size_limit", bytes)?;
+            Ok(())
+        })
+    }
+
+    /// Register the `regexp` function, which is needed to use [crate::Expr::matches_regex].
+    ///
+    /// Sqlite does not come with a regular expression engine, so it has to be provided with `compile`.
+    /// This function is called with the pattern and should return a matcher for that pattern.
+    /// The matcher is cached by sqlite, so a pattern is compiled only once per query.
+    /// With the [regex](https://docs.rs/regex) crate this looks like this:
+    /// ```ignore
+    /// let config = Config::open("my_database.sqlite").regexp(|pattern| {
+    ///     regex::Regex::new(pattern).map(|re| move |text: &str| re.is_match(text))
+    /// });
+    /// ```
+    ///
+    /// Returning an error from `compile` makes the query panic, just like other sql errors.
+    ///
+    /// ```
+    /// # use rust_query::{IntoExpr, migration::Config};
+    /// # use rust_query::private::doctest::*;
+    /// // a very simple "regex" engine that only supports literal patterns
+    /// let config = Config::open_in_memory().regexp(|pattern| {
+    ///     let pattern = pattern.to_owned();
+    ///     Ok::<_, std::convert::Infallible>(move |text: &str| text.contains(&pattern))
+    /// });
+    /// let mut client = get_client();
+    /// let db = client.migrator::<Empty>(config).unwrap().finish().unwrap();
+    /// let txn = client.transaction(&db);
+    /// assert!(txn.query_one("hello world".into_expr().matches_regex("o w")));
+    /// assert!(!txn.query_one("hello world".into_expr().matches_regex("ow")));
+    /// assert_eq!(txn.query_one(None::<String>.into_expr().matches_regex("o w")), None);
+    /// ```
+    pub fn regexp<R, E>(
+        self,
+        compile: impl Fn(&str) -> Result<R, E> + Send + Sync + 'static,
+    ) -> Self
+    where
+        R: Fn(&str) -> bool + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let compile = Arc::new(compile);
+        self.on_connect(move |inner| functions::register_regexp(inner, compile.clone()))
+    }
+
     /// Execute a raw sql statement if the database was just created.
     ///
     /// The statement is executed after creating the empty database and executingall previous statements.
@@ -293,13 +470,23 @@ impl Config {
     }
 }
 
+fn init_connection(inner: &mut Connection) -> rusqlite::Result<()> {
+    inner.pragma_update(None, "journal_mode", "WAL")?;
+    inner.pragma_update(None, "synchronous", "NORMAL")?;
+    inner.pragma_update(None, "foreign_keys", "ON")?;
+    inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DDL, false)?;
+    inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DML, false)?;
+    inner.set_db_config(DbConfig::SQLITE_DBCONFIG_DEFENSIVE, true)?;
+    functions::register_math(inner)?;
+    Ok(())
+}
+
 impl LocalClient {
     /// Create a [Migrator] to migrate a database.
     ///
     /// Returns [None] if the database `user_version` on disk is older than `S`.
     pub fn migrator<'t, S: Schema>(&'t mut self, config: Config) -> Option<Migrator<'t, S>> {
-        use r2d2::ManageConnection;
-        let conn = self.conn.insert(config.manager.connect().unwrap());
+        let conn = self.connect(&config.manager);
         conn.pragma_update(None, "foreign_keys", "OFF").unwrap();
 
         let conn = conn
@@ -309,11 +496,17 @@ impl LocalClient {
 
         // check if this database is newly created
         if schema_version(&conn) == 0 {
-            let mut b = TableTypBuilder::default();
-            S::typs(&mut b);
-
-            for (table_name, table) in &*b.ast.tables {
-                new_table_inner(&conn, table, Alias::new(table_name));
+            if let Some(sql) = &config.schema_sql {
+                conn.execute_batch(sql)
+                    .expect("raw sql statement from schema file failed");
+            } else {
+                let mut b = TableTypBuilder::default();
+                S::typs(&mut b);
+
+                for (table_name, table) in &*b.ast.tables {
+                    new_table_inner(&conn, table, Alias::new(table_name));
+                    new_indexes(&conn, table, table_name);
+                }
             }
             (config.init)(&conn);
             set_user_version(&conn, S::VERSION).unwrap();
@@ -344,6 +537,19 @@ impl LocalClient {
 ///
 /// When all migrations are done, it can be turned into a [Database] instance with
 /// [Migrator::finish].
+///
+/// # Tracking applied migrations
+///
+/// The version of the schema on disk is stored in the sqlite
+/// [`user_version`](https://www.sqlite.org/pragma.html#pragma_user_version) and
+/// every call to [Migrator::migrate] checks it, so only the pending migrations are applied.
+/// There is no separate metadata table, the full chain of migrations is always written
+/// in the code and steps that were applied before are skipped.
+///
+/// All migrations are applied in a single transaction, which is only committed by [Migrator::finish].
+/// If the application panics halfway, none of the migrations are applied.
+/// Rolling back migrations that were committed is not supported, because migrations
+/// can drop data that is needed to go back to the previous schema.
 pub struct Migrator<'t, S> {
     manager: r2d2_sqlite::SqliteConnectionManager,
     transaction: Rc<rusqlite::Transaction<'t>>,
@@ -389,6 +595,63 @@ impl<'t, S: Schema> Migrator<'t, S> {
         self,
         m: impl FnOnce(&mut TransactionMigrate<'t, S>) -> M,
     ) -> Migrator<'t, M::To>
+    where
+        M: SchemaMigration<'t, From = S>,
+    {
+        self.migrate_with_data(m, |_| {})
+    }
+
+    /// Same as [Migrator::migrate], but also runs `data` after the migration is applied.
+    ///
+    /// The `data` closure receives a [TransactionMut] for the new schema and is part of
+    /// the same transaction as the migration itself. It is only called when the migration is applied,
+    /// so it can be used to change existing data in ways that do not fit in the migration of a single row.
+    ///
+    /// Foreign keys are checked after `data` returns. This function panics if `data` left a foreign key
+    /// referencing a row that does not exist.
+    ///
+    /// ```
+    /// # use rust_query::migration::{schema, Config};
+    /// # use rust_query::{LocalClient, Table, aggregate};
+    /// #[schema(Schema)]
+    /// #[version(0..=1)]
+    /// pub mod vN {
+    ///     pub struct User {
+    ///         pub name: String,
+    ///     }
+    ///     #[version(1..)]
+    ///     pub struct UserCount {
+    ///         pub count: i64,
+    ///     }
+    /// }
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// let config = Config::open_in_memory().init_stmt("INSERT INTO user (name) VALUES ('Alice')");
+    /// let m = client.migrator(config).unwrap();
+    /// let m = m.migrate_with_data(
+    ///     |_| v0::migrate::Schema {},
+    ///     |txn| {
+    ///         let count = txn.query_one(aggregate(|rows| {
+    ///             v1::User::join(rows);
+    ///             rows.count_all()
+    ///         }));
+    ///         txn.insert_ok(v1::UserCount { count });
+    ///     },
+    /// );
+    /// let db = m.finish().unwrap();
+    /// let txn = client.transaction(&db);
+    /// let counts = txn.query(|rows| {
+    ///     let count = v1::UserCount::join(rows);
+    ///     rows.into_vec(count.count())
+    /// });
+    /// assert_eq!(counts, [1]);
+    /// # }
+    /// ```
+    pub fn migrate_with_data<M>(
+        self,
+        m: impl FnOnce(&mut TransactionMigrate<'t, S>) -> M,
+        data: impl FnOnce(&mut TransactionMut<'t, M::To>),
+    ) -> Migrator<'t, M::To>
     where
         M: SchemaMigration<'t, From = S>,
     {
@@ -413,10 +676,16 @@ impl<'t, S: Schema> Migrator<'t, S> {
                 let sql = drop.to_string(SqliteQueryBuilder);
                 self.transaction.execute(&sql, []).unwrap();
             }
+            let mut b = TableTypBuilder::default();
+            M::To::typs(&mut b);
             for (to, tmp) in builder.inner.rename_map {
                 let rename = sea_query::Table::rename().table(tmp, Alias::new(to)).take();
                 let sql = rename.to_string(SqliteQueryBuilder);
                 self.transaction.execute(&sql, []).unwrap();
+
+                // indexes are created after renaming, because their names are based on the table name
+                let (_, table) = b.ast.tables.iter().find(|(name, _)| name == to).unwrap();
+                new_indexes(&self.transaction, table, to);
             }
             if let Some(fk) = foreign_key_check(&self.transaction) {
                 (builder.foreign_key.remove(&*fk).unwrap())();
@@ -426,6 +695,14 @@ impl<'t, S: Schema> Migrator<'t, S> {
                 reason = "rustc is stupid and thinks this is unreachable"
             )]
             set_user_version(&self.transaction, M::To::VERSION).unwrap();
+
+            data(&mut TransactionMut {
+                inner: Transaction::new(self.transaction.clone()),
+            });
+            // foreign keys are not enforced during the migration, so check again
+            if let Some(fk) = foreign_key_check(&self.transaction) {
+                panic!("the data migration violated a foreign key constraint referencing `{fk}`");
+            }
         }
 
         Migrator {
@@ -0,0 +1,91 @@
+use std::fmt::Write;
+
+use crate::{Table, hash::TypBuilder};
+
+/// The query plan that sqlite uses for a query, as returned by `EXPLAIN QUERY PLAN`.
+///
+/// This is the result of [crate::args::Query::explain_plan].
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    pub(crate) nodes: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PlanNode {
+    pub(crate) id: i64,
+    pub(crate) parent: i64,
+    pub(crate) detail: String,
+}
+
+impl QueryPlan {
+    /// Render the plan as an indented tree, the same way as the `sqlite3` command line does.
+    pub fn tree(&self) -> String {
+        let mut out = "QUERY PLAN\n".to_owned();
+        self.write_children(&mut out, 0, "");
+        out
+    }
+
+    fn write_children(&self, out: &mut String, parent: i64, prefix: &str) {
+        let children: Vec<_> = self.nodes.iter().filter(|x| x.parent == parent).collect();
+        for (i, node) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let branch = if last { "`--" } else { "|--" };
+            writeln!(out, "{prefix}{branch}{}", node.detail).unwrap();
+            let indent = if last { "   " } else { "|  " };
+            self.write_children(out, node.id, &format!("{prefix}{indent}"));
+        }
+    }
+
+    /// Check if the plan uses an index on the table `T`.
+    ///
+    /// This works for indexes created with `#[index]` and for the indexes of unique constraints.
+    /// The index names are compared exactly, so indexes of other tables are never mistaken for indexes of `T`.
+    ///
+    /// ```
+    /// # use rust_query::{LocalClient, Table, migration::{Config, schema}};
+    /// #[schema(Schema)]
+    /// pub mod vN {
+    ///     #[table(name = "tbl_Artist")]
+    ///     pub struct Artist {
+    ///         #[unique]
+    ///         pub name: String,
+    ///     }
+    /// }
+    /// use v0::*;
+    ///
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let txn = client.transaction(&db);
+    /// let plan = txn.query(|rows| {
+    ///     let artist = Artist::join(rows);
+    ///     rows.filter(artist.name().eq("AC/DC"));
+    ///     rows.explain_plan(artist.name())
+    /// });
+    /// assert!(plan.uses_index::<Artist>());
+    ///
+    /// let plan = txn.query(|rows| {
+    ///     let artist = Artist::join(rows);
+    ///     rows.filter(artist.name().ends_with("DC"));
+    ///     rows.explain_plan(artist.name())
+    /// });
+    /// assert!(!plan.uses_index::<Artist>());
+    /// # }
+    /// ```
+    pub fn uses_index<T: Table>(&self) -> bool {
+        let mut b = TypBuilder::default();
+        T::typs(&mut b);
+        let names = b.ast.index_names(T::NAME);
+        self.nodes.iter().any(|x| {
+            let Some((_, rest)) = x.detail.split_once(" USING ") else {
+                return false;
+            };
+            let rest = rest.strip_prefix("COVERING ").unwrap_or(rest);
+            let Some(rest) = rest.strip_prefix("INDEX ") else {
+                return false;
+            };
+            let index = rest.split(' ').next().unwrap();
+            names.iter().any(|name| name == index)
+        })
+    }
+}
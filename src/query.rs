@@ -1,15 +1,22 @@
 use std::{
     cell::Cell,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
     marker::PhantomData,
     ops::{Deref, DerefMut},
 };
 
-use sea_query::SqliteQueryBuilder;
-use sea_query_rusqlite::RusqliteBinder;
+use sea_query::{Asterisk, Iden, Order, SelectStatement, SimpleExpr, SqliteQueryBuilder};
+use sea_query_rusqlite::{RusqliteBinder, RusqliteValues};
 
 use crate::{
+    IntoExpr,
+    alias::Field,
     dummy_impl::{Cacher, IntoSelect, Prepared, Row, SelectImpl},
+    plan::{PlanNode, QueryPlan},
     rows::Rows,
+    value::{EqTyp, Typed},
 };
 
 /// This is the type used by the [crate::Transaction::query] method.
@@ -17,8 +24,37 @@ pub struct Query<'outer, 'inner, S> {
     pub(crate) phantom: PhantomData<&'inner &'outer ()>,
     pub(crate) q: Rows<'inner, S>,
     pub(crate) conn: &'inner rusqlite::Connection,
+    pub(crate) limit: Option<u64>,
+    pub(crate) offset: Option<u64>,
+    pub(crate) distinct: Vec<Field>,
+    pub(crate) label: Option<String>,
+    pub(crate) first_n: Option<(u64, SimpleExpr)>,
+    pub(crate) order: Vec<(Field, Order)>,
 }
 
+/// The direction to sort in, used by [Query::order_by].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDir {
+    /// Smallest value first.
+    Asc,
+    /// Largest value first.
+    Desc,
+}
+
+/// The error returned by [Query::into_vec_at_most] when there are too many rows.
+#[derive(Debug)]
+pub struct TooManyRows {
+    max: usize,
+}
+
+impl Display for TooManyRows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "query returned more than {} rows", self.max)
+    }
+}
+
+impl std::error::Error for TooManyRows {}
+
 impl<'outer, 'inner, S> Deref for Query<'outer, 'inner, S> {
     type Target = Rows<'inner, S>;
 
@@ -43,11 +79,698 @@ impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
     /// The order of rows that is returned is unstable. This means that the order may change between any two
     /// executions of the exact same query. If a specific order (or even a consistent order) is required,
     /// then you have to use something like [slice::sort].
+    ///
+    /// Rows are decoded on the current thread while they are being fetched.
+    /// Decoding can not be moved to other threads, because results like [crate::TableRow]
+    /// are tied to the thread of the [crate::LocalClient]. Expensive processing of the
+    /// results should be done on the returned [Vec] instead.
     pub fn into_vec<O>(&self, select: impl IntoSelect<'inner, 'outer, S, Out = O>) -> Vec<O> {
         self.into_vec_private(select)
     }
 
+    /// Turn a database query into a rust [Vec] of at most `n` results.
+    ///
+    /// Unlike [Query::limit], this does not silently drop the extra rows.
+    /// Instead it fetches up to `n + 1` rows and returns [TooManyRows] if there are more than `n`.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec_at_most(2, user.name())
+    /// });
+    /// assert_eq!(names.unwrap().len(), 2);
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec_at_most(1, user.name())
+    /// });
+    /// assert!(names.is_err());
+    /// ```
+    pub fn into_vec_at_most<O>(
+        &self,
+        n: usize,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+    ) -> Result<Vec<O>, TooManyRows> {
+        let max = n as u64 + 1;
+        let limit = self.limit.map_or(max, |limit| limit.min(max));
+        let out = self.collect_private(select, |select| {
+            select.limit(limit);
+        });
+        if out.len() > n {
+            return Err(TooManyRows { max: n });
+        }
+        Ok(out)
+    }
+
+    /// Retrieve only the first result of the query.
+    ///
+    /// Returns [None] if there are no results.
+    /// Which row is first is determined by [Query::order_by],
+    /// without sorting an arbitrary row is returned.
+    ///
+    /// ```
+    /// # use rust_query::{SortDir, Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let name = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.order_by(user.name(), SortDir::Desc);
+    ///     rows.first(user.name())
+    /// });
+    /// assert_eq!(name.as_deref(), Some("Bob"));
+    /// ```
+    pub fn first<O>(&self, select: impl IntoSelect<'inner, 'outer, S, Out = O>) -> Option<O> {
+        let limit = self.limit.map_or(1, |limit| limit.min(1));
+        let out = self.collect_private(select, |select| {
+            select.limit(limit);
+        });
+        out.into_iter().next()
+    }
+
+    /// Turn a database query into a rust [Vec] of results, sorted by a key.
+    ///
+    /// The sorting happens in rust after all rows are retrieved, using [slice::sort_by_key].
+    /// This sort is stable, so rows with equal keys keep the order in which they were retrieved
+    /// (which is itself not consistent, see [Query::into_vec]).
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec_sorted(user.name(), |name| std::cmp::Reverse(name.clone()))
+    /// });
+    /// assert_eq!(names, ["Bob", "Alice"]);
+    /// ```
+    pub fn into_vec_sorted<O, K: Ord>(
+        &self,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+        key: impl FnMut(&O) -> K,
+    ) -> Vec<O> {
+        let mut out = self.into_vec_private(select);
+        out.sort_by_key(key);
+        out
+    }
+
+    /// Same as [Query::into_vec_sorted], but uses [slice::sort_unstable_by_key].
+    ///
+    /// This can be faster, but rows with equal keys are returned in arbitrary order.
+    pub fn into_vec_sorted_unstable<O, K: Ord>(
+        &self,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+        key: impl FnMut(&O) -> K,
+    ) -> Vec<O> {
+        let mut out = self.into_vec_private(select);
+        out.sort_unstable_by_key(key);
+        out
+    }
+
+    /// Turn a database query into a rust [Vec] of results, keeping only the first result for each key.
+    ///
+    /// This is useful when `DISTINCT` would deduplicate on too many columns.
+    /// Which of the duplicate results is kept is arbitrary (see [Query::into_vec]).
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Anna" }).unwrap();
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec_dedup(user.name(), |name| name.chars().next())
+    /// });
+    /// assert_eq!(names.len(), 2);
+    /// ```
+    pub fn into_vec_dedup<O, K: Hash + Eq>(
+        &self,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+        mut key: impl FnMut(&O) -> K,
+    ) -> Vec<O> {
+        let mut seen = HashSet::new();
+        let mut out = self.into_vec_private(select);
+        out.retain(|val| seen.insert(key(val)));
+        out
+    }
+
+    /// Group the results by a key and combine each group in rust.
+    ///
+    /// Every group starts with the value returned by `init` and every result is
+    /// then combined into the accumulator of its group with `fold`.
+    /// This can be used for aggregates that can not be expressed in sql.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Anna" }).unwrap();
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let groups = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.group_fold(user.name().starts_with("A"), user.name(), Vec::new, |acc, name| {
+    ///         acc.push(name)
+    ///     })
+    /// });
+    /// assert_eq!(groups[&true].len(), 2);
+    /// assert_eq!(groups[&false], ["Bob"]);
+    /// ```
+    pub fn group_fold<K: Hash + Eq, O, A>(
+        &self,
+        key: impl IntoSelect<'inner, 'outer, S, Out = K>,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+        mut init: impl FnMut() -> A,
+        mut fold: impl FnMut(&mut A, O),
+    ) -> HashMap<K, A> {
+        let mut out = HashMap::new();
+        for (key, val) in self.into_vec_private((key, select)) {
+            fold(out.entry(key).or_insert_with(&mut init), val);
+        }
+        out
+    }
+
+    /// Turn a database query into an [Iterator] of [Vec]s with `chunk_size` results each.
+    ///
+    /// The last chunk can have fewer than `chunk_size` results, but it is never empty.
+    /// Every chunk is retrieved with a separate query using `LIMIT` and `OFFSET`,
+    /// so at most `chunk_size` results are held in memory at a time.
+    /// Use [Query::order_by] to make sure that the order of rows is the same for every chunk.
+    /// Note that sqlite still needs to step over the skipped rows, so later chunks take longer to retrieve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    ///
+    /// ```
+    /// # use rust_query::{SortDir, Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let sizes: Vec<usize> = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec_chunks(user.name(), 2).map(|chunk| chunk.len()).collect()
+    /// });
+    /// assert_eq!(sizes, [2, 1]);
+    ///
+    /// txn.insert(User { name: "Dave" }).unwrap();
+    /// let chunks: Vec<Vec<String>> = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.order_by(user.name(), SortDir::Asc);
+    ///     rows.offset(1);
+    ///     rows.limit(2);
+    ///     rows.into_vec_chunks(user.name(), 1).collect()
+    /// });
+    /// assert_eq!(chunks, [["Bob"], ["Charlie"]]);
+    /// ```
+    pub fn into_vec_chunks<O>(
+        &self,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Vec<O>> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        let mut cacher = Cacher::new();
+        let mut prepared = select.into_select().inner.prepare(&mut cacher);
+        let cached = self.ast.cache(cacher.columns);
+        self.check_distinct();
+
+        let mut pos = self.offset.unwrap_or(0);
+        let mut end = self.limit.map(|limit| pos.saturating_add(limit));
+        std::iter::from_fn(move || {
+            let size = end.map_or(chunk_size as u64, |end| {
+                end.saturating_sub(pos).min(chunk_size as u64)
+            });
+            if size == 0 {
+                return None;
+            }
+            let mut select = self.filtered();
+            self.apply_order(&mut select);
+            select.limit(size).offset(pos);
+
+            let chunk = self.execute(&select, |row| prepared.call(Row::new(row, &cached)));
+            pos += chunk.len() as u64;
+            if (chunk.len() as u64) < size {
+                // there are no more rows
+                end = Some(pos);
+            }
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
+    /// Only include the first `n` rows of each group, ordered by `order_by`.
+    ///
+    /// The groups are made of rows with the same value for `partition_by`.
+    /// Rows with the same `order_by` value in a group are ranked in an unspecified order.
+    /// Like [Query::limit], this is applied after all filters.
+    /// Calling this method again will replace the previous limit per group.
+    ///
+    /// ```
+    /// # use rust_query::{LocalClient, Table, migration::{Config, schema}};
+    /// #[schema(Schema)]
+    /// pub mod vN {
+    ///     pub struct Track {
+    ///         pub genre: String,
+    ///         pub name: String,
+    ///         pub rank: i64,
+    ///     }
+    /// }
+    /// use v0::*;
+    ///
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&db);
+    /// txn.insert_ok(Track { genre: "Rock", name: "A", rank: 2 });
+    /// txn.insert_ok(Track { genre: "Rock", name: "B", rank: 1 });
+    /// txn.insert_ok(Track { genre: "Rock", name: "C", rank: 3 });
+    /// txn.insert_ok(Track { genre: "Jazz", name: "D", rank: 1 });
+    ///
+    /// let mut top = txn.query(|rows| {
+    ///     let track = Track::join(rows);
+    ///     rows.first_n(2, track.rank(), track.genre());
+    ///     rows.into_vec(track.name())
+    /// });
+    /// top.sort();
+    /// assert_eq!(top, ["A", "B", "D"]);
+    /// # }
+    /// ```
+    pub fn first_n(
+        &mut self,
+        n: u64,
+        order_by: impl IntoExpr<'inner, S>,
+        partition_by: impl IntoExpr<'inner, S>,
+    ) {
+        let b = self.ast.builder();
+        let order_by = order_by.into_expr().inner.build_expr(b);
+        let partition_by = partition_by.into_expr().inner.build_expr(b);
+        let row_number = sea_query::Expr::cust_with_exprs(
+            "ROW_NUMBER() OVER (PARTITION BY ? ORDER BY ?)",
+            [partition_by, order_by],
+        );
+        self.first_n = Some((n, row_number));
+    }
+
+    /// Retrieve one row for each distinct value of `partition_by`, like `DISTINCT ON` in PostgreSQL.
+    ///
+    /// The row with the smallest `order_by` value is chosen for each value of `partition_by`.
+    /// This is the same as using [Query::first_n] with `n = 1` followed by [Query::into_vec].
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Anna" }).unwrap();
+    /// let mut names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     let first_letter = user.name().glob("A*");
+    ///     rows.distinct_on(first_letter, user.name(), user.name())
+    /// });
+    /// names.sort();
+    /// assert_eq!(names, ["Alice", "Bob"]);
+    /// ```
+    pub fn distinct_on<O>(
+        &mut self,
+        partition_by: impl IntoExpr<'inner, S>,
+        order_by: impl IntoExpr<'inner, S>,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+    ) -> Vec<O> {
+        self.first_n(1, order_by, partition_by);
+        self.into_vec_private(select)
+    }
+
+    /// Attach a label to the sql of this query.
+    ///
+    /// The label is added as a comment in front of the sql, which makes it possible
+    /// to find out where a query came from when the sql shows up in logs.
+    /// Calling this method again will replace the previous label.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let txn = get_txn(&mut client);
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.annotate("user_names");
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Alice"]);
+    /// ```
+    pub fn annotate(&mut self, label: &str) {
+        // the label can not be allowed to end the comment early
+        self.label = Some(label.replace("*/", "* /"));
+    }
+
+    /// Sort the results by `expr`.
+    ///
+    /// Calling this method multiple times will sort by the first expression first,
+    /// rows with the same value are then sorted by the next expression.
+    /// The order of rows with the same value for all expressions is unspecified.
+    ///
+    /// ```
+    /// # use rust_query::{SortDir, Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.order_by(user.name(), SortDir::Desc);
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Bob", "Alice"]);
+    /// ```
+    pub fn order_by(&mut self, expr: impl IntoExpr<'inner, S>, dir: SortDir) {
+        let field = self.ast.cache([expr.into_expr().inner.erase()])[0];
+        let order = match dir {
+            SortDir::Asc => Order::Asc,
+            SortDir::Desc => Order::Desc,
+        };
+        self.order.push((field, order));
+    }
+
+    /// Sort the results like [Query::order_by], but only if `order` is [Some].
+    ///
+    /// ```
+    /// # use rust_query::{SortDir, Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let list = |dir: Option<SortDir>| {
+    ///     txn.query(|rows| {
+    ///         let user = User::join(rows);
+    ///         rows.maybe_order_by(dir.map(|dir| (user.name(), dir)));
+    ///         rows.into_vec(user.name())
+    ///     })
+    /// };
+    /// assert_eq!(list(Some(SortDir::Asc)), ["Alice", "Bob"]);
+    /// assert_eq!(list(Some(SortDir::Desc)), ["Bob", "Alice"]);
+    /// assert_eq!(list(None).len(), 2);
+    /// ```
+    pub fn maybe_order_by(&mut self, order: Option<(impl IntoExpr<'inner, S>, SortDir)>) {
+        if let Some((expr, dir)) = order {
+            self.order_by(expr, dir);
+        }
+    }
+
+    /// Limit the number of rows that are returned.
+    ///
+    /// The limit is applied after all filters, no matter in which order they are added.
+    /// Calling this method again will replace the previous limit.
+    ///
+    /// Note that without [Query::offset] the limit will select an arbitrary subset of the rows.
+    pub fn limit(&mut self, n: u64) {
+        self.limit = Some(n);
+    }
+
+    /// Limit the number of rows like [Query::limit], but only if `limit` is [Some].
+    ///
+    /// This will not remove a limit that was set previously when `limit` is [None].
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let count = |limit: Option<u64>| {
+    ///     txn.query(|rows| {
+    ///         let user = User::join(rows);
+    ///         rows.maybe_limit(limit);
+    ///         rows.into_vec(user).len()
+    ///     })
+    /// };
+    /// assert_eq!(count(Some(1)), 1);
+    /// assert_eq!(count(None), 2);
+    /// ```
+    pub fn maybe_limit(&mut self, limit: Option<u64>) {
+        if let Some(n) = limit {
+            self.limit(n);
+        }
+    }
+
+    /// Skip the first `n` rows that would otherwise be returned.
+    ///
+    /// This is usually combined with [Query::limit] for pagination.
+    /// Calling this method again will replace the previous offset.
+    pub fn offset(&mut self, n: u64) {
+        self.offset = Some(n);
+    }
+
+    /// Check that every result of this query has a different value for `key`.
+    ///
+    /// In debug builds, retrieving the results will panic with the duplicate value if the check fails.
+    /// In release builds this method does nothing.
+    /// This is useful to find data integrity bugs when a unique constraint is not practical.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.require_distinct(user.name());
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names.len(), 2);
+    /// ```
+    ///
+    /// ```should_panic
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Albert" }).unwrap();
+    /// txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.require_distinct(user.name().starts_with("A"));
+    ///     rows.into_vec(user.name())
+    /// });
+    /// ```
+    pub fn require_distinct<T: EqTyp + 'static>(&mut self, key: impl IntoExpr<'inner, S, Typ = T>) {
+        if cfg!(debug_assertions) {
+            let key = key.into_expr().inner.erase();
+            self.distinct.extend(self.ast.cache([key]));
+        }
+    }
+
+    fn check_distinct(&self) {
+        for field in &self.distinct {
+            let alias = self.ast.scope.new_alias();
+            let mut select = SelectStatement::new();
+            select
+                .column(*field)
+                .from_subquery(self.limited(), alias)
+                .group_by_col(*field)
+                .and_having(sea_query::Expr::expr(sea_query::Expr::col(Asterisk).count()).gt(1))
+                .limit(1);
+            let (sql, values) = self.build(&select);
+
+            let mut statement = self.conn.prepare_cached(&sql).unwrap();
+            let mut rows = statement.query(&*values.as_params()).unwrap();
+            if let Some(row) = rows.next().unwrap() {
+                let val: rusqlite::types::Value = row.get(0).unwrap();
+                panic!("query results are not distinct, duplicate value: {val:?}");
+            }
+        }
+    }
+
+    /// Turn a database query into a rust [Vec] of results together with the total number of rows.
+    ///
+    /// The total is the number of rows that would be returned without [Query::limit] and [Query::offset].
+    /// It is retrieved in the same query using `COUNT(*) OVER ()`, only when the page is empty
+    /// is a separate query needed to find the total.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let (page, total) = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.limit(2);
+    ///     rows.into_vec_with_total(user.name())
+    /// });
+    /// assert_eq!(page.len(), 2);
+    /// assert_eq!(total, 3);
+    ///
+    /// let (page, total) = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.offset(5);
+    ///     rows.into_vec_with_total(user.name())
+    /// });
+    /// assert!(page.is_empty());
+    /// assert_eq!(total, 3);
+    ///
+    /// let (page, total) = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.first_n(1, user.name(), user.name().starts_with("A"));
+    ///     rows.limit(1);
+    ///     rows.into_vec_with_total(user.name())
+    /// });
+    /// assert_eq!(page.len(), 1);
+    /// assert_eq!(total, 2, "one user starting with A and one other user");
+    /// ```
+    pub fn into_vec_with_total<O>(
+        &self,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+    ) -> (Vec<O>, u64) {
+        let field = self.ast.scope.new_field();
+        let mut total = None;
+        let out = self.collect_inspect(
+            select,
+            |select| {
+                // the total is counted after all filters (including [Query::first_n]),
+                // but before the limit and offset are applied
+                *select = SelectStatement::new()
+                    .column(Asterisk)
+                    .expr_as(sea_query::Expr::cust("COUNT(*) OVER ()"), field)
+                    .from_subquery(self.filtered(), self.ast.scope.new_alias())
+                    .to_owned();
+                self.apply_limit(select);
+            },
+            |row| total = Some(row.get_unwrap::<_, i64>(&*field.to_string()) as u64),
+        );
+
+        let total = total.unwrap_or_else(|| self.count_private());
+        (out, total)
+    }
+
+    fn count_private(&self) -> u64 {
+        let mut select = SelectStatement::new();
+        select
+            .expr(sea_query::Expr::col(Asterisk).count())
+            .from_subquery(self.filtered(), self.ast.scope.new_alias());
+        let (sql, values) = self.build(&select);
+        if SHOW_SQL.get() {
+            println!("{sql}");
+            println!("{values:?}");
+        }
+
+        let mut statement = self.conn.prepare_cached(&sql).unwrap();
+        statement
+            .query_row(&*values.as_params(), |row| row.get::<_, i64>(0))
+            .unwrap() as u64
+    }
+
+    /// Retrieve the plan that sqlite would use to execute [Query::into_vec] with `select`.
+    ///
+    /// This is useful to check that a query uses an index.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let txn = get_txn(&mut client);
+    /// let plan = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.filter(user.name().eq("Alice"));
+    ///     rows.explain_plan(user.name())
+    /// });
+    /// assert!(plan.uses_index::<User>());
+    /// assert!(plan.tree().starts_with("QUERY PLAN\n`--SEARCH"));
+    /// ```
+    pub fn explain_plan<O>(
+        &self,
+        select: impl IntoSelect<'inner, 'outer, S, Out = O>,
+    ) -> QueryPlan {
+        let mut cacher = Cacher::new();
+        let _ = select.into_select().inner.prepare(&mut cacher);
+        self.ast.cache(cacher.columns);
+
+        let (sql, values) = self.build(&self.limited());
+        let mut statement = self
+            .conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {sql}"))
+            .unwrap();
+        let nodes = statement
+            .query_map(&*values.as_params(), |row| {
+                Ok(PlanNode {
+                    id: row.get(0)?,
+                    parent: row.get(1)?,
+                    detail: row.get(3)?,
+                })
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        QueryPlan { nodes }
+    }
+
+    fn build(&self, select: &SelectStatement) -> (String, RusqliteValues) {
+        let (sql, values) = select.build_rusqlite(SqliteQueryBuilder);
+        match &self.label {
+            Some(label) => (format!("/* {label} */ {sql}"), values),
+            None => (sql, values),
+        }
+    }
+
+    fn filtered(&self) -> SelectStatement {
+        let mut select = self.ast.simple();
+        if let Some((n, row_number)) = &self.first_n {
+            // window functions can not be used in filters, so a sub-query is needed
+            let field = self.ast.scope.new_field();
+            select.expr_as(row_number.clone(), field);
+            select = SelectStatement::new()
+                .column(Asterisk)
+                .from_subquery(select, self.ast.scope.new_alias())
+                .and_where(sea_query::Expr::col(field).lte(*n))
+                .to_owned();
+        }
+        select
+    }
+
+    fn limited(&self) -> SelectStatement {
+        let mut select = self.filtered();
+        self.apply_limit(&mut select);
+        select
+    }
+
+    fn apply_order(&self, select: &mut SelectStatement) {
+        for (field, order) in &self.order {
+            select.order_by(*field, order.clone());
+        }
+    }
+
+    fn apply_limit(&self, select: &mut SelectStatement) {
+        self.apply_order(select);
+        if let Some(offset) = self.offset {
+            // sqlite requires a limit when an offset is used
+            select.limit(self.limit.unwrap_or(i64::MAX as u64));
+            select.offset(offset);
+        } else if let Some(limit) = self.limit {
+            select.limit(limit);
+        }
+    }
+
     pub(crate) fn into_vec_private<'x, D>(&self, dummy: D) -> Vec<D::Out>
+    where
+        D: IntoSelect<'x, 'outer, S>,
+    {
+        self.collect_private(dummy, |_| {})
+    }
+
+    pub(crate) fn collect_private<'x, D>(
+        &self,
+        dummy: D,
+        modify: impl FnOnce(&mut SelectStatement),
+    ) -> Vec<D::Out>
+    where
+        D: IntoSelect<'x, 'outer, S>,
+    {
+        self.collect_inspect(dummy, modify, |_| {})
+    }
+
+    /// Same as [Query::collect_private], but `inspect` is called with every raw row.
+    fn collect_inspect<'x, D>(
+        &self,
+        dummy: D,
+        modify: impl FnOnce(&mut SelectStatement),
+        mut inspect: impl FnMut(&rusqlite::Row),
+    ) -> Vec<D::Out>
     where
         D: IntoSelect<'x, 'outer, S>,
     {
@@ -55,9 +778,22 @@ impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
         let mut prepared = dummy.into_select().inner.prepare(&mut cacher);
 
         let cached = self.ast.cache(cacher.columns);
+        self.check_distinct();
 
-        let select = self.ast.simple();
-        let (sql, values) = select.build_rusqlite(SqliteQueryBuilder);
+        let mut select = self.limited();
+        modify(&mut select);
+        self.execute(&select, |row| {
+            inspect(row);
+            prepared.call(Row::new(row, &cached))
+        })
+    }
+
+    fn execute<R>(
+        &self,
+        select: &SelectStatement,
+        mut f: impl FnMut(&rusqlite::Row) -> R,
+    ) -> Vec<R> {
+        let (sql, values) = self.build(select);
         if SHOW_SQL.get() {
             println!("{sql}");
             println!("{values:?}");
@@ -68,7 +804,7 @@ impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
 
         let mut out = vec![];
         while let Some(row) = rows.next().unwrap() {
-            out.push(prepared.call(Row::new(row, &cached)));
+            out.push(f(row));
         }
         out
     }
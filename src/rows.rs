@@ -3,11 +3,13 @@ use std::marker::PhantomData;
 use sea_query::{Iden, SimpleExpr};
 
 use crate::{
-    Expr, Table,
+    Expr, FromExpr, Select, Table, TableRow, aggregate,
     alias::TmpTable,
+    args::Aggregate,
     ast::MySelect,
     db::Join,
-    value::{IntoExpr, Typed},
+    optional,
+    value::{EqTyp, IntoExpr, MyTyp, Typed},
 };
 
 /// [Rows] keeps track of all rows in the current query.
@@ -31,6 +33,25 @@ impl<'inner, S> Rows<'inner, S> {
     /// (Also called the "Carthesian product")
     ///
     /// For convenience there is also [Table::join].
+    ///
+    /// Filters can use values from all joined tables, so a join can depend on the rows of
+    /// earlier joins. This gives the same result as a `LATERAL JOIN` in other databases.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let pairs = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     // for each user, join all other users
+    ///     let other = User::join(rows);
+    ///     rows.filter(other.eq(&user).not());
+    ///     rows.into_vec((user.name(), other.name()))
+    /// });
+    /// assert_eq!(pairs.len(), 6);
+    /// ```
     pub fn join<T: Table<Schema = S>>(&mut self) -> Expr<'inner, S, T> {
         let alias = self.ast.scope.new_alias();
         self.ast.tables.push((T::NAME.to_owned(), alias));
@@ -51,6 +72,67 @@ impl<'inner, S> Rows<'inner, S> {
         Expr::new(Join::new(alias))
     }
 
+    /// Join a table and select all of its columns.
+    ///
+    /// This is the typed version of `SELECT *`. The result is the table struct with all
+    /// the column values filled in, just like [Transaction::get](crate::Transaction::get).
+    ///
+    /// ```
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let users = txn.query(|rows| {
+    ///     let user = rows.select_all_columns::<User>();
+    ///     rows.into_vec(user)
+    /// });
+    /// let names: Vec<_> = users.into_iter().map(|user| user.name).collect();
+    /// assert_eq!(names, ["Alice", "Bob"]);
+    /// ```
+    pub fn select_all_columns<'t, T: Table<Schema = S>>(
+        &mut self,
+    ) -> Select<'inner, 't, S, T::Full<'t>> {
+        T::Full::from_expr(self.join::<T>())
+    }
+
+    /// Join a table, but only include the rows from `ids`.
+    ///
+    /// This is useful to retrieve data for many [TableRow]s at once.
+    /// If `ids` is empty, then no rows are included.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// let bob = txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let names = txn.query(|rows| {
+    ///     let user = rows.filter_by_ids(&[bob]);
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Bob"]);
+    ///
+    /// let count = txn.query(|rows| {
+    ///     let user = rows.filter_by_ids::<User>(&[]);
+    ///     rows.into_vec(user).len()
+    /// });
+    /// assert_eq!(count, 0);
+    /// ```
+    pub fn filter_by_ids<T: Table<Schema = S>>(
+        &mut self,
+        ids: &[TableRow<'inner, T>],
+    ) -> Expr<'inner, S, T> {
+        let row = self.join::<T>();
+        let id = row.inner.build_expr(self.ast.builder());
+        let cond = if ids.is_empty() {
+            sea_query::Expr::val(false).into()
+        } else {
+            sea_query::Expr::expr(id).is_in(ids.iter().map(|x| x.inner.idx))
+        };
+        self.filter_private(cond);
+        row
+    }
+
     // Join a vector of values.
     // pub fn vec<V: IntoExpr<'inner>>(&mut self, vec: Vec<V>) -> Join<'inner, V::Typ> {
     //     todo!()
@@ -62,10 +144,281 @@ impl<'inner, S> Rows<'inner, S> {
         self.filter_private(prop.build_expr(self.ast.builder()));
     }
 
+    /// Filter rows based on a column, but only if `cond` is [Some].
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let search = |prefix: Option<&str>| {
+    ///     txn.query(|rows| {
+    ///         let user = User::join(rows);
+    ///         rows.maybe_filter(prefix.map(|prefix| user.name().starts_with(prefix)));
+    ///         rows.into_vec(user.name())
+    ///     })
+    /// };
+    /// assert_eq!(search(Some("B")), ["Bob"]);
+    /// assert_eq!(search(None).len(), 2);
+    /// ```
+    pub fn maybe_filter(&mut self, cond: Option<impl IntoExpr<'inner, S, Typ = bool>>) {
+        if let Some(cond) = cond {
+            self.filter(cond);
+        }
+    }
+
+    /// Filter rows based on a dynamic list of conditions, at least one of which must be true.
+    ///
+    /// If `conds` is empty, then no rows are included.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let search = ["Alice", "Charlie"];
+    /// let mut names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.filter_any(search.map(|name| user.name().eq(name)));
+    ///     rows.into_vec(user.name())
+    /// });
+    /// names.sort();
+    /// assert_eq!(names, ["Alice", "Charlie"]);
+    /// ```
+    pub fn filter_any(
+        &mut self,
+        conds: impl IntoIterator<Item = impl IntoExpr<'inner, S, Typ = bool>>,
+    ) {
+        let b = self.ast.builder();
+        let cond = conds
+            .into_iter()
+            .map(|x| x.into_expr().inner.build_expr(b))
+            .reduce(|a, b| a.or(b))
+            .unwrap_or(sea_query::Expr::val(false).into());
+        self.filter_private(cond);
+    }
+
+    /// Filter rows based on a dynamic list of conditions, all of which must be true.
+    ///
+    /// If `conds` is empty, then all rows are included.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Alex" }).unwrap();
+    /// let prefixes = ["Al", "Ale"];
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.filter_all(prefixes.map(|prefix| user.name().starts_with(prefix)));
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Alex"]);
+    /// ```
+    pub fn filter_all(
+        &mut self,
+        conds: impl IntoIterator<Item = impl IntoExpr<'inner, S, Typ = bool>>,
+    ) {
+        for cond in conds {
+            self.filter(cond);
+        }
+    }
+
+    /// Only include rows where `expr` is equal to one of the `values`.
+    ///
+    /// If `values` is empty, then no rows are included.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let mut names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.filter_in(user.name(), ["Alice", "Charlie"]);
+    ///     rows.into_vec(user.name())
+    /// });
+    /// names.sort();
+    /// assert_eq!(names, ["Alice", "Charlie"]);
+    /// ```
+    pub fn filter_in<T: EqTyp + 'static>(
+        &mut self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        values: impl IntoIterator<Item = impl IntoExpr<'inner, S, Typ = T>>,
+    ) {
+        let b = self.ast.builder();
+        let values: Vec<_> = values
+            .into_iter()
+            .map(|x| x.into_expr().inner.build_expr(b))
+            .collect();
+        let cond = if values.is_empty() {
+            sea_query::Expr::val(false).into()
+        } else {
+            sea_query::Expr::expr(expr.into_expr().inner.build_expr(b)).is_in(values)
+        };
+        self.filter_private(cond);
+    }
+
+    /// Only include rows where `expr` is not equal to any of the `values`.
+    ///
+    /// This is the complement of [Rows::filter_in]. If `values` is empty, then all rows are included.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.filter_not_in(user.name(), ["Alice", "Charlie"]);
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Bob"]);
+    /// ```
+    pub fn filter_not_in<T: EqTyp + 'static>(
+        &mut self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        values: impl IntoIterator<Item = impl IntoExpr<'inner, S, Typ = T>>,
+    ) {
+        let b = self.ast.builder();
+        let values: Vec<_> = values
+            .into_iter()
+            .map(|x| x.into_expr().inner.build_expr(b))
+            .collect();
+        if !values.is_empty() {
+            let expr = expr.into_expr().inner.build_expr(b);
+            self.filter_private(sea_query::Expr::expr(expr).is_not_in(values));
+        }
+    }
+
+    /// Only include rows for which the sub-query has no rows.
+    ///
+    /// This is the `NOT EXISTS` pattern, the sub-query can use [Aggregate::filter_on]
+    /// to only consider rows that are related to the current row.
+    ///
+    /// ```
+    /// # use rust_query::{LocalClient, Table, migration::{Config, schema}};
+    /// #[schema(Schema)]
+    /// pub mod vN {
+    ///     pub struct Customer {
+    ///         pub name: String,
+    ///     }
+    ///     pub struct Invoice {
+    ///         pub customer: Customer,
+    ///     }
+    /// }
+    /// use v0::*;
+    ///
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&db);
+    /// let alice = txn.insert(Customer { name: "Alice" }).unwrap();
+    /// txn.insert(Customer { name: "Bob" }).unwrap();
+    /// txn.insert(Invoice { customer: alice }).unwrap();
+    ///
+    /// let never_ordered = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.anti_join(|rows| {
+    ///         let invoice = Invoice::join(rows);
+    ///         rows.filter_on(invoice.customer(), &customer);
+    ///     });
+    ///     rows.into_vec(customer.name())
+    /// });
+    /// assert_eq!(never_ordered, ["Bob"]);
+    ///
+    /// let ordered = txn.query(|rows| {
+    ///     let customer = Customer::join(rows);
+    ///     rows.semi_join(|rows| {
+    ///         let invoice = Invoice::join(rows);
+    ///         rows.filter_on(invoice.customer(), &customer);
+    ///     });
+    ///     rows.into_vec(customer.name())
+    /// });
+    /// assert_eq!(ordered, ["Alice"]);
+    /// # }
+    /// ```
+    pub fn anti_join(&mut self, f: impl for<'a> FnOnce(&mut Aggregate<'inner, 'a, S>))
+    where
+        S: 'static,
+    {
+        let exists = aggregate(|rows| {
+            f(rows);
+            rows.exists()
+        });
+        self.filter(exists.not());
+    }
+
+    /// Only include rows for which the sub-query has at least one row.
+    ///
+    /// This is the `EXISTS` pattern and the counterpart of [Rows::anti_join].
+    /// Unlike joining the related table, this never duplicates rows.
+    /// See [Rows::anti_join] for an example that uses both.
+    pub fn semi_join(&mut self, f: impl for<'a> FnOnce(&mut Aggregate<'inner, 'a, S>))
+    where
+        S: 'static,
+    {
+        let exists = aggregate(|rows| {
+            f(rows);
+            rows.exists()
+        });
+        self.filter(exists);
+    }
+
     fn filter_private(&mut self, prop: SimpleExpr) {
         self.ast.filters.push(Box::new(prop));
     }
 
+    /// Select from the row referenced by an optional foreign key, without filtering out rows.
+    ///
+    /// This is like a `LEFT JOIN`: the result is [None] when `fk` is [None].
+    /// It is a shorthand for using [crate::optional] with a single [Optional::and](crate::args::Optional::and).
+    ///
+    /// ```
+    /// # use rust_query::{IntoSelect, Table, TableRow, migration::{Config, schema}};
+    /// #[schema(Schema)]
+    /// pub mod vN {
+    ///     pub struct Team {
+    ///         pub name: String,
+    ///     }
+    ///     pub struct Employee {
+    ///         pub name: String,
+    ///         pub team: Option<Team>,
+    ///     }
+    /// }
+    /// use v0::*;
+    ///
+    /// # fn main() {
+    /// # let mut client = rust_query::LocalClient::try_new().unwrap();
+    /// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&db);
+    /// let sales = txn.insert_ok(Team { name: "Sales" });
+    /// txn.insert_ok(Employee { name: "Alice", team: None::<TableRow<_>> });
+    /// txn.insert_ok(Employee { name: "Bob", team: Some(sales) });
+    ///
+    /// let teams = txn.query(|rows| {
+    ///     let employee = Employee::join(rows);
+    ///     let team = rows.join_optional_and_select(employee.team(), |team| team.name().into_select());
+    ///     rows.into_vec((employee.name(), team))
+    /// });
+    /// assert_eq!(teams, [("Alice".to_owned(), None), ("Bob".to_owned(), Some("Sales".to_owned()))]);
+    /// # }
+    /// ```
+    pub fn join_optional_and_select<'t, T: MyTyp, O: 't>(
+        &self,
+        fk: impl IntoExpr<'inner, S, Typ = Option<T>>,
+        f: impl for<'a> FnOnce(Expr<'a, S, T>) -> Select<'a, 't, S, O>,
+    ) -> Select<'inner, 't, S, Option<O>> {
+        let fk = fk.into_expr();
+        optional(|row| {
+            let val = row.and(fk);
+            row.then(f(val))
+        })
+    }
+
     /// Filter out rows where this column is [None].
     ///
     /// Returns a new column with the unwrapped type.
@@ -37,6 +37,7 @@ macro_rules! table {
             type UpdateOk<'t> = ();
             type Update<'t> = ();
             type Insert<'t> = ();
+            type Full<'t> = ();
 
             fn read<'t>(_val: &Self::Insert<'t>, _f: &Reader<'t, Self::Schema>) {
                 unreachable!()
@@ -60,6 +61,7 @@ macro_rules! table {
                 unreachable!()
             }
 
+            const COLUMNS: &'static [crate::migrate::ColumnMeta] = &[];
             const ID: &'static str = "";
             const NAME: &'static str = "";
         }
@@ -111,7 +113,7 @@ struct ForeignKeyListSelect<T>(T);
 impl ForeignKeyListSelect<Expr<'_, Pragma, ForeignKeyList>> {
     field! {table: String}
     field! {from: String}
-    field! {to: String}
+    field! {to: Option<String>}
 }
 
 table! {ForeignKeyList, ForeignKeyListSelect, val => format!("pragma_foreign_key_list('{}', 'main')", val.0)}
@@ -181,6 +183,7 @@ pub fn read_schema(conn: &Transaction<Pragma>) -> hash::Schema {
             "INTEGER" => hash::ColumnType::Integer,
             "TEXT" => hash::ColumnType::String,
             "REAL" => hash::ColumnType::Float,
+            "BLOB" => hash::ColumnType::Blob,
             t => panic!("unknown type {t}"),
         };
 
@@ -213,20 +216,269 @@ pub fn read_schema(conn: &Transaction<Pragma>) -> hash::Schema {
         });
 
         for unique_name in uniques {
-            let columns = conn.query(|q| {
-                let col = q.join_custom(IndexInfo(unique_name));
-                let name = q.filter_some(col.name());
-                q.into_vec(name)
-            });
-
             let mut unique_def = hash::Unique::default();
-            for column in columns {
+            for column in index_columns(conn, unique_name) {
                 unique_def.columns.insert(column);
             }
             table_def.uniques.insert(unique_def);
         }
 
+        let indexes = conn.query(|q| {
+            let index = q.join_custom(IndexList(table_name.clone()));
+            q.filter(index.unique().not());
+            q.filter(index.origin().eq("c"));
+            q.filter(index.partial().not());
+            q.into_vec(index.name())
+        });
+
+        for index_name in indexes {
+            let mut index_def = hash::Index::default();
+            for column in index_columns(conn, index_name) {
+                index_def.columns.insert(column);
+            }
+            table_def.indexes.insert(index_def);
+        }
+
         output.tables.insert((table_name, table_def))
     }
     output
 }
+
+fn index_columns(conn: &Transaction<Pragma>, index_name: String) -> Vec<String> {
+    conn.query(|q| {
+        let col = q.join_custom(IndexInfo(index_name));
+        let name = q.filter_some(col.name());
+        q.into_vec(name)
+    })
+}
+
+/// Generate a [crate::migration::schema] definition from an existing database.
+///
+/// This is meant as a starting point for using rust-query with a database that
+/// was not created by rust-query, the generated code needs manual review.
+/// Column types are chosen with the sqlite type affinity rules and columns
+/// or constraints that can not be represented are added as comments.
+///
+/// ```
+/// let conn = rusqlite::Connection::open_in_memory().unwrap();
+/// conn.execute_batch(
+///     "CREATE TABLE artist (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);
+///     CREATE TABLE album (
+///         id INTEGER PRIMARY KEY,
+///         title VARCHAR(100) NOT NULL,
+///         artist INTEGER NOT NULL REFERENCES artist,
+///         year INTEGER
+///     );",
+/// )
+/// .unwrap();
+/// let code = rust_query::migration::scaffold_schema(&conn);
+/// assert_eq!(
+///     code,
+///     "#[rust_query::migration::schema(Schema)]
+/// pub mod vN {
+///     pub struct Album {
+///         pub artist: Artist,
+///         pub title: String,
+///         pub year: Option<i64>,
+///     }
+///     pub struct Artist {
+///         #[unique]
+///         pub name: String,
+///     }
+/// }
+/// "
+/// );
+/// ```
+pub fn scaffold_schema(conn: &rusqlite::Connection) -> String {
+    #[derive(Clone, FromExpr)]
+    #[rust_query(From = TableInfo)]
+    struct Column {
+        name: String,
+        r#type: String,
+        pk: i64,
+        notnull: i64,
+    }
+
+    let txn = conn.unchecked_transaction().unwrap();
+    let conn = &Transaction::<Pragma>::new(std::rc::Rc::new(txn));
+
+    let tables = conn.query(|q| {
+        let table = q.join_custom(TableList);
+        q.filter(table.schema().eq("main"));
+        q.filter(table.r#type().eq("table"));
+        q.filter(table.name().starts_with("sqlite_").not());
+        q.into_vec(table.name())
+    });
+    let mut tables: Vec<_> = tables
+        .into_iter()
+        .map(|name| (scaffold_struct_name(&name), name))
+        .collect();
+    tables.sort();
+
+    let mut out = "#[rust_query::migration::schema(Schema)]\npub mod vN {\n".to_owned();
+    for (struct_name, table_name) in &tables {
+        if !is_simple_name(table_name) {
+            out.push_str(&format!("    #[table(name = {table_name:?})]\n"));
+        }
+
+        let unique_indexes = conn.query(|q| {
+            let index = q.join_custom(IndexList(table_name.clone()));
+            q.filter(index.unique());
+            q.filter(index.origin().eq("pk").not());
+            q.filter(index.partial().not());
+            q.into_vec(index.name())
+        });
+        let mut uniques = vec![];
+        for index_name in unique_indexes {
+            let columns = index_columns(conn, index_name);
+            if columns.len() > 1 {
+                let fields: Vec<_> = columns.iter().map(|c| scaffold_field_name(c)).collect();
+                out.push_str(&format!(
+                    "    #[unique_{}({})]\n",
+                    fields.join("_"),
+                    fields.join(", ")
+                ));
+            }
+            uniques.push(columns);
+        }
+        let indexes = conn.query(|q| {
+            let index = q.join_custom(IndexList(table_name.clone()));
+            q.filter(index.unique().not());
+            q.filter(index.partial().not());
+            q.into_vec(index.name())
+        });
+        let indexes: Vec<_> = indexes
+            .into_iter()
+            .map(|index_name| index_columns(conn, index_name))
+            .collect();
+
+        let fks: HashMap<_, _> = conn
+            .query(|q| {
+                let fk = q.join_custom(ForeignKeyList(table_name.to_owned()));
+                q.into_vec((fk.from(), (fk.table(), fk.to())))
+            })
+            .into_iter()
+            .collect();
+
+        out.push_str(&format!("    pub struct {struct_name} {{\n"));
+        let mut columns: Vec<Column> = conn.query(|q| {
+            let table = q.join_custom(TableInfo(table_name.clone()));
+            q.into_vec(Column::from_expr(table))
+        });
+        columns.sort_by(|a, b| a.name.cmp(&b.name));
+        for col in columns {
+            if col.pk != 0 {
+                if col.name != "id" || !col.r#type.eq_ignore_ascii_case("INTEGER") {
+                    out.push_str(&format!(
+                        "        // primary key `{} {}` is replaced by the `id` column\n",
+                        col.name, col.r#type
+                    ));
+                }
+                continue;
+            }
+            if col.name.eq_ignore_ascii_case("id") {
+                out.push_str(&format!(
+                    "        // column `{} {}` can not be used because the name is reserved\n",
+                    col.name, col.r#type
+                ));
+                continue;
+            }
+            let field_name = scaffold_field_name(&col.name);
+            if field_name != col.name {
+                out.push_str(&format!("        #[column(name = {:?})]\n", col.name));
+            }
+            if uniques.contains(&vec![col.name.clone()]) {
+                out.push_str("        #[unique]\n");
+            }
+            if indexes.contains(&vec![col.name.clone()]) {
+                out.push_str("        #[index]\n");
+            }
+            let mut typ = match fks.get(&col.name) {
+                Some((table, to)) if to.is_none() || to.as_deref() == Some("id") => {
+                    scaffold_struct_name(table)
+                }
+                Some((table, to)) => {
+                    out.push_str(&format!(
+                        "        // references column `{}` of table `{table}`\n",
+                        to.as_deref().unwrap_or_default()
+                    ));
+                    affinity_type(&col.r#type).to_owned()
+                }
+                None => affinity_type(&col.r#type).to_owned(),
+            };
+            if col.notnull == 0 {
+                typ = format!("Option<{typ}>");
+            }
+            out.push_str(&format!("        pub {field_name}: {typ},\n"));
+        }
+        for index in indexes.iter().filter(|index| index.len() > 1) {
+            out.push_str(&format!(
+                "        // index on ({}) is not supported\n",
+                index.join(", ")
+            ));
+        }
+        out.push_str("    }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+// https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+fn affinity_type(typ: &str) -> &'static str {
+    let typ = typ.to_uppercase();
+    if typ.contains("INT") {
+        "i64"
+    } else if typ.contains("CHAR") || typ.contains("CLOB") || typ.contains("TEXT") {
+        "String"
+    } else if typ.is_empty() || typ.contains("BLOB") {
+        "Vec<u8>"
+    } else {
+        "f64"
+    }
+}
+
+fn is_simple_name(name: &str) -> bool {
+    name.split('_')
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase()))
+}
+
+fn scaffold_struct_name(table_name: &str) -> String {
+    let mut out = String::new();
+    for part in table_name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.push(first.to_ascii_uppercase());
+            out.extend(chars.map(|c| c.to_ascii_lowercase()));
+        }
+    }
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        out.insert(0, 'T');
+    }
+    out
+}
+
+fn scaffold_field_name(column_name: &str) -> String {
+    let mut out: String = column_name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' => c,
+            'A'..='Z' => c.to_ascii_lowercase(),
+            _ => '_',
+        })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_lowercase() || c == '_') || is_keyword(&out) {
+        out.insert(0, 'c');
+        out.insert(1, '_');
+    }
+    out
+}
+
+fn is_keyword(name: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else",
+        "enum", "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match",
+        "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait",
+        "true", "try", "type", "unsafe", "use", "where", "while", "yield",
+    ];
+    KEYWORDS.contains(&name)
+}
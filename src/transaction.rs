@@ -8,8 +8,14 @@ use sea_query::{
 use sea_query_rusqlite::RusqliteBinder;
 
 use crate::{
-    IntoExpr, IntoSelect, Table, TableRow, ast::MySelect, client::LocalClient,
-    migrate::schema_version, private::Reader, query::Query, rows::Rows, value::SecretFromSql,
+    FromExpr, IntoExpr, IntoSelect, Table, TableRow,
+    ast::MySelect,
+    client::LocalClient,
+    migrate::{Schema, schema_version},
+    private::Reader,
+    query::Query,
+    rows::Rows,
+    value::SecretFromSql,
     writable::TableInsert,
 };
 
@@ -53,6 +59,12 @@ impl<S> Database<S> {
 ///
 /// All [TableRow] references retrieved from the database live for at most `'a`.
 /// This makes these references effectively local to this [Transaction].
+///
+/// A [Transaction] is already a consistent snapshot of the database, there is no separate snapshot type.
+/// From the first query on, all queries in the same [Transaction] see the same state,
+/// even when other [TransactionMut]s are committed in the meantime.
+/// Note that sqlite can not checkpoint the WAL past the oldest snapshot that is still in use,
+/// so long running [Transaction]s should be dropped when they are no longer needed.
 pub struct Transaction<'t, S> {
     pub(crate) transaction: Rc<rusqlite::Transaction<'t>>,
     pub(crate) _p: PhantomData<fn(&'t ()) -> &'t ()>,
@@ -99,6 +111,38 @@ impl<'t, S> Transaction<'t, S> {
         Self::new(Rc::new(txn))
     }
 
+    /// Execute a raw sql query and map each result row with `f`.
+    ///
+    /// This is an escape hatch for queries that can not be expressed with the typed API.
+    /// None of the sql is type checked and the result columns have to be retrieved by index or name.
+    /// Only read-only statements are allowed, use [TransactionWeak::execute_raw] for modifications.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the statement can modify the database.
+    ///
+    /// ```
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let txn = get_txn(&mut client);
+    /// let names = txn
+    ///     .query_raw("SELECT name FROM user WHERE name LIKE ?", ["A%"], |row| {
+    ///         row.get::<_, String>(0)
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(names, ["Alice"]);
+    /// ```
+    pub fn query_raw<R>(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+        f: impl FnMut(&rusqlite::Row) -> rusqlite::Result<R>,
+    ) -> rusqlite::Result<Vec<R>> {
+        let mut stmt = self.transaction.prepare_cached(sql)?;
+        assert!(stmt.readonly(), "raw queries must be read-only");
+        stmt.query_map(params, f)?.collect()
+    }
+
     /// Execute a query with multiple results.
     ///
     /// ```
@@ -129,6 +173,12 @@ impl<'t, S> Transaction<'t, S> {
             q,
             phantom: PhantomData,
             conn,
+            limit: None,
+            offset: None,
+            distinct: Vec::new(),
+            label: None,
+            first_n: None,
+            order: Vec::new(),
         })
     }
 
@@ -154,6 +204,155 @@ impl<'t, S> Transaction<'t, S> {
         });
         res.pop().unwrap()
     }
+
+    /// Retrieve all columns of a single row.
+    ///
+    /// The returned value is the table struct with all the column values filled in.
+    /// A [TableRow] always refers to an existing row, so this can not fail.
+    ///
+    /// ```
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// let alice = txn.query_one(User::unique("Alice")).unwrap();
+    /// let user = txn.get(alice);
+    /// assert_eq!(user.name, "Alice");
+    /// ```
+    pub fn get<T: Table<Schema = S>>(&self, row: TableRow<'t, T>) -> T::Full<'t> {
+        self.query_one(T::Full::from_expr(row))
+    }
+}
+
+impl<'t, S: Schema> Transaction<'t, S> {
+    /// Export the content of all tables as sql `INSERT` statements.
+    ///
+    /// Tables are ordered such that referenced tables come first and rows keep their `id`.
+    /// This makes it possible to recreate the exact same state by executing the statements
+    /// on an empty database with the same schema.
+    ///
+    /// If a table references itself (or tables reference each other), then the output starts
+    /// with `PRAGMA defer_foreign_keys = ON;`. The foreign keys are then only checked when the
+    /// transaction is committed, so the statements have to be executed inside a transaction.
+    ///
+    /// ```
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let txn = get_txn(&mut client);
+    /// assert_eq!(txn.dump_sql(), "INSERT INTO \"user\" (\"id\", \"name\") VALUES (1, 'Alice');\n");
+    /// ```
+    ///
+    /// The output is also useful as a test fixture.
+    /// Store it in a file and load it into a new database with [crate::migration::Config::init_stmt].
+    /// Rows that reference other rows need the [TableRow] of the referenced row when inserting them
+    /// with the typed API, so a fixture in sql is the easiest way to set up many related rows at once.
+    ///
+    /// ```
+    /// # use rust_query::{Table, migration::Config};
+    /// # use rust_query::private::doctest::*;
+    /// const FIXTURE: &str = "INSERT INTO \"user\" (\"id\", \"name\") VALUES (1, 'Bob');";
+    /// let config = Config::open_in_memory().init_stmt(FIXTURE);
+    /// let mut client = get_client();
+    /// let db = client.migrator::<Empty>(config).unwrap().finish().unwrap();
+    /// let txn = client.transaction(&db);
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec(user.name())
+    /// });
+    /// assert_eq!(names, ["Bob"]);
+    /// ```
+    pub fn dump_sql(&self) -> String {
+        let mut tables: Vec<_> = S::TABLES.iter().collect();
+        let mut done: Vec<&str> = vec![];
+        let mut out = String::new();
+        // rows are inserted in `id` order, so a self reference can point to a later row
+        let mut defer = false;
+        while !tables.is_empty() {
+            let pos = tables.iter().position(|table| {
+                (table.columns.iter())
+                    .filter_map(|col| col.references)
+                    .all(|name| name == table.name || done.contains(&name))
+            });
+            // a cycle between tables can only be inserted with deferred foreign key checks
+            defer |= pos.is_none();
+            let table = tables.remove(pos.unwrap_or(0));
+            defer |= (table.columns.iter()).any(|col| col.references == Some(table.name));
+            done.push(table.name);
+
+            let columns: Vec<_> = std::iter::once("id")
+                .chain(table.columns.iter().map(|col| col.name))
+                .map(|name| format!("\"{}\"", name.replace('"', "\"\"")))
+                .collect();
+            let columns = columns.join(", ");
+            let table_name = format!("\"{}\"", table.name.replace('"', "\"\""));
+
+            let sql = format!("SELECT {columns} FROM {table_name} ORDER BY \"id\"");
+            let mut stmt = self.transaction.prepare(&sql).unwrap();
+            let mut rows = stmt.query([]).unwrap();
+            while let Some(row) = rows.next().unwrap() {
+                let values: Vec<_> = (0..=table.columns.len())
+                    .map(|i| sql_literal(row.get_ref(i).unwrap()))
+                    .collect();
+                out.push_str(&format!(
+                    "INSERT INTO {table_name} ({columns}) VALUES ({});\n",
+                    values.join(", ")
+                ));
+            }
+        }
+        if defer {
+            out.insert_str(0, "PRAGMA defer_foreign_keys = ON;\n");
+        }
+        out
+    }
+
+    /// Panic if any of the tables in the schema contains rows.
+    ///
+    /// This is meant for tests that need to verify that the database is fresh before seeding it.
+    /// The panic message lists all tables that are not empty.
+    ///
+    /// ```
+    /// # use rust_query::migration::Config;
+    /// # use rust_query::private::doctest::*;
+    /// let mut client = get_client();
+    /// let db = client.migrator::<Empty>(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// client.transaction(&db).assert_empty();
+    /// ```
+    pub fn assert_empty(&self) {
+        let non_empty: Vec<_> = S::TABLES
+            .iter()
+            .map(|table| table.name)
+            .filter(|name| {
+                let sql = format!(
+                    "SELECT EXISTS(SELECT 1 FROM \"{}\")",
+                    name.replace('"', "\"\"")
+                );
+                (self.transaction)
+                    .query_row(&sql, [], |row| row.get::<_, bool>(0))
+                    .unwrap()
+            })
+            .collect();
+        assert!(
+            non_empty.is_empty(),
+            "expected database to be empty, but these tables have rows: {}",
+            non_empty.join(", ")
+        );
+    }
+}
+
+fn sql_literal(value: rusqlite::types::ValueRef) -> String {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => "NULL".to_owned(),
+        ValueRef::Integer(val) => val.to_string(),
+        ValueRef::Real(val) if val.is_infinite() => {
+            if val > 0. { "9e999" } else { "-9e999" }.to_owned()
+        }
+        ValueRef::Real(val) => format!("{val:?}"),
+        ValueRef::Text(val) => format!("'{}'", String::from_utf8_lossy(val).replace('\'', "''")),
+        ValueRef::Blob(val) => {
+            let hex: String = val.iter().map(|byte| format!("{byte:02x}")).collect();
+            format!("X'{hex}'")
+        }
+    }
 }
 
 impl<'t, S: 'static> TransactionMut<'t, S> {
@@ -307,6 +506,74 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         }
     }
 
+    /// Update multiple rows, each with their own new column values.
+    ///
+    /// This has the same semantics as calling [TransactionMut::update] for each row in order,
+    /// with one [Result] per row. Updates that fail do not prevent later updates from being applied.
+    ///
+    /// ```
+    /// # use rust_query::{private::doctest::*, Table, Update};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// let alice = txn.query_one(User::unique("Alice")).unwrap();
+    /// let bob = txn.insert(User { name: "Bob" }).unwrap();
+    /// let res = txn.update_many([
+    ///     (alice, User { name: Update::set("Alicia") }),
+    ///     (bob, User { name: Update::set("Alicia") }),
+    /// ]);
+    /// assert!(res[0].is_ok());
+    /// assert!(res[1].is_err(), "the name is already used by alice");
+    /// ```
+    pub fn update_many<T: Table<Schema = S>>(
+        &mut self,
+        updates: impl IntoIterator<Item = (TableRow<'t, T>, T::Update<'t>)>,
+    ) -> Vec<Result<(), T::Conflict<'t>>> {
+        updates
+            .into_iter()
+            .map(|(row, val)| self.update(row, val))
+            .collect()
+    }
+
+    /// Apply the same update to all rows for which `cond` is true.
+    ///
+    /// The update is created with `val` for every matching row and then applied
+    /// like [TransactionMut::update], one row at a time.
+    /// This returns the number of updated rows, or the conflict of the first row that could not be updated.
+    /// Rows that were updated before the conflict stay updated,
+    /// use [TransactionMut::within_savepoint] to undo them.
+    ///
+    /// ```
+    /// # use rust_query::{private::doctest::*, Table, Update};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let updated = txn.update_all_where::<User>(
+    ///     |user| user.name().starts_with("B"),
+    ///     || User { name: Update::set("Robert") },
+    /// );
+    /// assert_eq!(updated, Ok(1));
+    /// let names = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.into_vec_sorted(user.name(), |name| name.clone())
+    /// });
+    /// assert_eq!(names, ["Alice", "Robert"]);
+    /// ```
+    pub fn update_all_where<T: Table<Schema = S>>(
+        &mut self,
+        cond: impl for<'a> Fn(crate::Expr<'a, S, T>) -> crate::Expr<'a, S, bool>,
+        mut val: impl FnMut() -> T::Update<'t>,
+    ) -> Result<usize, T::Conflict<'t>> {
+        let rows = self.query(|rows| {
+            let row = T::join(rows);
+            rows.filter(cond(row.clone()));
+            rows.into_vec(row)
+        });
+        for row in &rows {
+            self.update(*row, val())?;
+        }
+        Ok(rows.len())
+    }
+
     /// This is a convenience function to use [TransactionMut::update] for updates
     /// that can not cause unique constraint violations.
     ///
@@ -325,6 +592,57 @@ impl<'t, S: 'static> TransactionMut<'t, S> {
         }
     }
 
+    /// Run `f` inside a savepoint with the given name.
+    ///
+    /// If `f` returns [Ok], then the changes made by `f` are kept.
+    /// If `f` returns [Err], then all changes made by `f` are rolled back, while
+    /// the changes from before the savepoint are kept.
+    ///
+    /// The closure gets a [TransactionMut] with a new lifetime, this makes sure that
+    /// [TableRow]s that are created inside the savepoint can not be used after a roll back.
+    /// It also means that [TableRow]s from outside the savepoint can not be used inside,
+    /// use [crate::OwnedTableRow] if you need that.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// let res = txn.within_savepoint("add_users", |txn| {
+    ///     txn.insert(User { name: "Bob" }).unwrap();
+    ///     txn.insert(User { name: "Alice" }).map_err(|_| "Alice already exists")?;
+    ///     Ok::<_, &str>(())
+    /// });
+    /// assert_eq!(res, Err("Alice already exists"));
+    /// let count = txn.query(|rows| {
+    ///     User::join(rows);
+    ///     rows.into_vec(()).len()
+    /// });
+    /// assert_eq!(count, 1, "Bob was not added");
+    /// ```
+    pub fn within_savepoint<R, E>(
+        &mut self,
+        name: &str,
+        f: impl for<'x> FnOnce(&mut TransactionMut<'x, S>) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let name = name.replace('"', "\"\"");
+        let conn = &self.inner.transaction;
+        conn.execute_batch(&format!("SAVEPOINT \"{name}\""))
+            .unwrap();
+
+        let mut inner = TransactionMut {
+            inner: Transaction::new(self.inner.transaction.clone()),
+        };
+        let res = f(&mut inner);
+        drop(inner);
+
+        if res.is_err() {
+            conn.execute_batch(&format!("ROLLBACK TO \"{name}\""))
+                .unwrap();
+        }
+        conn.execute_batch(&format!("RELEASE \"{name}\"")).unwrap();
+        res
+    }
+
     /// Make the changes made in this [TransactionMut] permanent.
     ///
     /// If the [TransactionMut] is dropped without calling this function, then the changes are rolled back.
@@ -399,6 +717,66 @@ impl<'t, S: 'static> TransactionWeak<'t, S> {
         res
     }
 
+    /// Try to delete multiple rows from the database with a single statement.
+    ///
+    /// When this method returns [Ok] it contains the number of rows that were deleted.
+    /// Rows that were deleted previously in this transaction are not counted.
+    ///
+    /// If any of the rows is still referenced by another row, then none of the rows are deleted.
+    /// The [Err] then contains the first row that could not be deleted.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// let bob = txn.insert(User { name: "Bob" }).unwrap();
+    /// let alice = txn.query_one(User::unique("Alice")).unwrap();
+    /// let mut txn = txn.downgrade();
+    /// assert_eq!(txn.delete_many(&[alice, bob]), Ok(2));
+    /// assert_eq!(txn.delete_many(&[alice]), Ok(0));
+    /// assert_eq!(txn.delete_many::<User>(&[]), Ok(0));
+    /// ```
+    pub fn delete_many<T: Table<Schema = S>>(
+        &mut self,
+        rows: &[TableRow<'t, T>],
+    ) -> Result<u64, TableRow<'t, T>> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let stmt = DeleteStatement::new()
+            .from_table(Alias::new(T::NAME))
+            .cond_where(Expr::col(Alias::new(T::ID)).is_in(rows.iter().map(|row| row.inner.idx)))
+            .to_owned();
+
+        let (query, args) = stmt.build_rusqlite(SqliteQueryBuilder);
+        let mut stmt = self.inner.transaction.prepare(&query).unwrap();
+
+        match stmt.execute(&*args.as_params()) {
+            Ok(n) => Ok(n as u64),
+            Err(rusqlite::Error::SqliteFailure(kind, Some(_val)))
+                if kind.code == ErrorCode::ConstraintViolation =>
+            {
+                // Some foreign key constraint got violated, find out which row caused it.
+                let conn = &self.inner.transaction;
+                let failing = rows.iter().find(|row| {
+                    let stmt = DeleteStatement::new()
+                        .from_table(Alias::new(T::NAME))
+                        .cond_where(Expr::col(Alias::new(T::ID)).eq(row.inner.idx))
+                        .to_owned();
+                    let (query, args) = stmt.build_rusqlite(SqliteQueryBuilder);
+
+                    conn.execute_batch("SAVEPOINT delete_many").unwrap();
+                    let res = conn.execute(&query, &*args.as_params());
+                    conn.execute_batch("ROLLBACK TO delete_many; RELEASE delete_many")
+                        .unwrap();
+                    res.is_err()
+                });
+                Err(*failing.expect("one of the rows should violate a constraint"))
+            }
+            Err(err) => panic!("{err}"),
+        }
+    }
+
     /// This allows you to do (almost) anything you want with the internal [rusqlite::Transaction].
     ///
     /// Note that there are some things that you should not do with the transaction, such as:
@@ -411,6 +789,28 @@ impl<'t, S: 'static> TransactionWeak<'t, S> {
         &self.inner.transaction
     }
 
+    /// Execute a raw sql statement and return the number of rows that were changed.
+    ///
+    /// This is an escape hatch for statements that can not be expressed with the typed API.
+    /// None of the sql is type checked, so the same restrictions as for [TransactionWeak::rusqlite_transaction] apply.
+    ///
+    /// ```
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let txn = get_txn(&mut client);
+    /// let mut txn = txn.downgrade();
+    /// let changed = txn.execute_raw("UPDATE user SET name = upper(name)", ()).unwrap();
+    /// assert_eq!(changed, 1);
+    /// ```
+    pub fn execute_raw(
+        &mut self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> rusqlite::Result<u64> {
+        let mut stmt = self.inner.transaction.prepare_cached(sql)?;
+        stmt.execute(params).map(|n| n as u64)
+    }
+
     /// Make the changes made in this [TransactionWeak] permanent.
     ///
     /// If the [TransactionWeak] is dropped without calling this function, then the changes are rolled back.
@@ -419,6 +819,132 @@ impl<'t, S: 'static> TransactionWeak<'t, S> {
     }
 }
 
+/// Buffers inserts and writes them with multi-row `INSERT` statements.
+///
+/// Rows are written when the buffer reaches `batch_size` rows, when [BulkInserter::flush] is called
+/// and when the [BulkInserter] is dropped.
+/// Only tables without unique constraints are supported, because those inserts can not conflict.
+/// Writing can still fail for other reasons, an error when writing on drop is printed as a warning,
+/// call [BulkInserter::flush] before dropping to handle the error instead.
+///
+/// ```
+/// # use rust_query::{BulkInserter, LocalClient, Table, migration::{Config, schema}};
+/// #[schema(Schema)]
+/// pub mod vN {
+///     pub struct Event {
+///         pub name: String,
+///     }
+/// }
+/// use v0::*;
+///
+/// # fn main() {
+/// # let mut client = LocalClient::try_new().unwrap();
+/// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+/// # let mut txn = client.transaction_mut(&db);
+/// let mut inserter = BulkInserter::new(&mut txn, 100);
+/// for i in 0..250 {
+///     inserter.push(Event { name: format!("event {i}") }).unwrap();
+/// }
+/// inserter.flush().unwrap();
+/// drop(inserter);
+///
+/// let names = txn.query(|rows| {
+///     let event = Event::join(rows);
+///     rows.into_vec(event.name())
+/// });
+/// assert_eq!(names.len(), 250);
+/// assert_eq!(names[249], "event 249");
+/// # }
+/// ```
+pub struct BulkInserter<'a, 't, S, T: Table<Schema = S, Conflict<'t> = Infallible>> {
+    txn: &'a mut TransactionMut<'t, S>,
+    batch_size: usize,
+    buffer: Vec<T::Insert<'t>>,
+}
+
+impl<'a, 't, S, T: Table<Schema = S, Conflict<'t> = Infallible>> BulkInserter<'a, 't, S, T> {
+    /// Create a new [BulkInserter] that writes to the transaction in batches of `batch_size` rows.
+    ///
+    /// This panics if `batch_size` is zero.
+    pub fn new(txn: &'a mut TransactionMut<'t, S>, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be at least one");
+        Self {
+            txn,
+            batch_size,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Add a row to the buffer, this writes the buffer if it is full.
+    ///
+    /// The error of writing the buffer is returned, see [BulkInserter::flush].
+    pub fn push(&mut self, val: impl TableInsert<'t, T = T>) -> rusqlite::Result<()> {
+        self.buffer.push(val.into_insert());
+        if self.buffer.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// The transaction that rows are written to.
+    ///
+    /// This can be used to query the rows that have been written so far.
+    pub fn transaction(&self) -> &TransactionMut<'t, S> {
+        self.txn
+    }
+
+    /// Write all the buffered rows.
+    ///
+    /// If writing fails, then all rows that were not written stay in the buffer,
+    /// so calling this method again will retry them.
+    pub fn flush(&mut self) -> rusqlite::Result<()> {
+        // sqlite has a limit on the number of selects in a compound select
+        const MAX_COMPOUND: usize = 500;
+
+        while !self.buffer.is_empty() {
+            let len = self.buffer.len().min(MAX_COMPOUND);
+
+            let mut names = vec![];
+            let mut select: Option<SelectStatement> = None;
+            for val in &self.buffer[..len] {
+                let ast = MySelect::default();
+                T::read(val, Reader::new(&ast));
+                names = ast.select.iter().map(|(_field, name)| *name).collect();
+                let row = ast.simple();
+                match &mut select {
+                    Some(select) => {
+                        select.union(sea_query::UnionType::All, row);
+                    }
+                    None => select = Some(row),
+                }
+            }
+
+            let mut insert = InsertStatement::new();
+            insert.into_table(Alias::new(T::NAME));
+            insert.columns(names);
+            insert.select_from(select.unwrap()).unwrap();
+
+            let (sql, values) = insert.build_rusqlite(SqliteQueryBuilder);
+            let mut statement = self.txn.inner.transaction.prepare_cached(&sql)?;
+            statement.execute(&*values.as_params())?;
+            self.buffer.drain(..len);
+        }
+        Ok(())
+    }
+}
+
+impl<'t, S, T: Table<Schema = S, Conflict<'t> = Infallible>> Drop for BulkInserter<'_, 't, S, T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        // errors can not be returned from drop, so they are only reported
+        if let Err(err) = self.flush() {
+            eprintln!("warning: BulkInserter failed to insert rows: {err}");
+        }
+    }
+}
+
 pub fn try_insert_private<'t, T: Table>(
     transaction: &Rc<rusqlite::Transaction<'t>>,
     table: sea_query::TableRef,
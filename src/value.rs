@@ -1,7 +1,20 @@
+#[macro_use]
+mod stored_as;
+
 pub mod aggregate;
+#[cfg(feature = "chrono")]
+mod chrono_impl;
+pub mod computed;
+pub mod date;
 mod operations;
 pub mod optional;
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_impl;
+#[cfg(feature = "time")]
+mod time_impl;
 pub mod trivial;
+#[cfg(feature = "uuid")]
+mod uuid_impl;
 
 use std::{fmt::Debug, marker::PhantomData, ops::Deref, rc::Rc};
 
@@ -56,7 +69,20 @@ impl<'x> ValueBuilder<'x> {
     }
 }
 
-pub trait NumTyp: MyTyp + Clone + Copy {
+/// Types that have the same ordering in sqlite as they have in rust.
+#[diagnostic::on_unimplemented(
+    message = "Columns with type `{Self}` can not be compared with `<` and `>`"
+)]
+pub trait OrdTyp: MyTyp {}
+
+impl OrdTyp for String {}
+impl OrdTyp for Vec<u8> {}
+impl OrdTyp for i64 {}
+impl OrdTyp for f64 {}
+#[diagnostic::do_not_recommend]
+impl<T: Table> OrdTyp for T {}
+
+pub trait NumTyp: OrdTyp + Clone + Copy {
     const ZERO: Self;
     fn into_sea_value(self) -> sea_query::Value;
 }
@@ -74,6 +100,45 @@ impl NumTyp for f64 {
     }
 }
 
+/// Types that support string operations, this is [String] and [`Option<String>`].
+///
+/// String operations on [`Option<String>`] return [None] when the input is [None].
+#[diagnostic::on_unimplemented(message = "Columns with type `{Self}` can not be used as text")]
+pub trait Textual: MyTyp {
+    type Bool: MyTyp;
+    type Text: MyTyp;
+}
+
+impl Textual for String {
+    type Bool = bool;
+    type Text = String;
+}
+impl Textual for Option<String> {
+    type Bool = Option<bool>;
+    type Text = Option<String>;
+}
+
+/// Numeric types that can be used in arithmetic with `Rhs`.
+///
+/// Arithmetic between [i64] and [f64] results in [f64], just like in sqlite.
+#[diagnostic::on_unimplemented(message = "Can not use `{Self}` in arithmetic with `{Rhs}`")]
+pub trait Numeric<Rhs: NumTyp = Self>: NumTyp {
+    type Output: NumTyp;
+}
+
+impl Numeric<i64> for i64 {
+    type Output = i64;
+}
+impl Numeric<f64> for i64 {
+    type Output = f64;
+}
+impl Numeric<i64> for f64 {
+    type Output = f64;
+}
+impl Numeric<f64> for f64 {
+    type Output = f64;
+}
+
 #[diagnostic::on_unimplemented(
     message = "Columns with type `{Self}` can not be checked for equality",
     note = "`EqTyp` is also implemented for all table types"
@@ -387,6 +452,10 @@ impl<'t, T: SecretFromSql<'t>> SecretFromSql<'t> for Option<T> {
 /// - And finally the type paramter `T` specifies the type of the expression.
 ///
 /// [Expr] implements [Deref] to have table extension methods in case the type is a table type.
+///
+/// There is no need to give an [Expr] a name like `expr AS alias` in sql to use it multiple times.
+/// Just clone it instead. The same expression is only computed once when it is selected multiple times
+/// or used in [crate::args::Query::order_by], because those refer to the same column in the generated sql.
 pub struct Expr<'column, S, T> {
     pub(crate) inner: DynTyped<T>,
     pub(crate) _p: PhantomData<&'column ()>,
@@ -5,14 +5,14 @@ use std::{
 };
 
 use ref_cast::RefCast;
-use sea_query::{Func, SelectStatement, SimpleExpr};
+use sea_query::{ExprTrait, Func, Nullable, SelectStatement, SimpleExpr};
 
 use crate::{
     Expr, Table,
     alias::{Field, MyAlias},
     ast::MySelect,
     rows::Rows,
-    value::{EqTyp, IntoExpr, MyTyp, NumTyp, Typed, ValueBuilder},
+    value::{EqTyp, IntoExpr, MyTyp, NumTyp, OrdTyp, Typed, ValueBuilder},
 };
 
 /// This is the argument type used for [aggregate].
@@ -85,6 +85,120 @@ impl<'outer, 'inner, S: 'static> Aggregate<'outer, 'inner, S> {
         Expr::new(self.select(expr))
     }
 
+    /// Return the value of `val` in the row with the maximum value of `key`, this is [None] if there are zero rows.
+    ///
+    /// If multiple rows have the maximum `key`, then the value from one of them is returned.
+    ///
+    /// ```
+    /// # use rust_query::{LocalClient, Table, aggregate, migration::{Config, schema}};
+    /// #[schema(Schema)]
+    /// pub mod vN {
+    ///     pub struct Player {
+    ///         pub name: String,
+    ///         pub score: i64,
+    ///     }
+    /// }
+    /// use v0::*;
+    ///
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&db);
+    /// txn.insert_ok(Player { name: "Alice", score: 10 });
+    /// txn.insert_ok(Player { name: "Bob", score: 30 });
+    /// txn.insert_ok(Player { name: "Charlie", score: 20 });
+    ///
+    /// let best = txn.query_one(aggregate(|rows| {
+    ///     let player = Player::join(rows);
+    ///     rows.max_by(player.name(), player.score())
+    /// }));
+    /// assert_eq!(best.as_deref(), Some("Bob"));
+    /// # }
+    /// ```
+    pub fn max_by<V, K>(
+        &self,
+        val: impl IntoExpr<'inner, S, Typ = V>,
+        key: impl IntoExpr<'inner, S, Typ = K>,
+    ) -> Expr<'outer, S, Option<V>>
+    where
+        V: MyTyp<Sql: Nullable>,
+        K: OrdTyp,
+    {
+        let b = self.ast.builder();
+        let val = val.into_expr().inner.build_expr(b);
+        let key = key.into_expr().inner.build_expr(b);
+
+        // This uses a separate sub-query, because sqlite only takes bare columns
+        // from the row with the maximum value if there is a single `max` in the query.
+        let mut select = self.query.ast.build_select(true);
+        select.clear_selects();
+        for (group, alias) in self.ast.filter_on.iter() {
+            select.expr_as(group.clone(), *alias);
+        }
+        let field = self.ast.scope.new_field();
+        select.expr_as(val, field);
+        select.expr(Func::max(key));
+
+        Expr::new(Aggr::<S, Option<V>> {
+            _p2: PhantomData,
+            select,
+            field,
+            conds: self.conds.clone(),
+        })
+    }
+
+    /// Return the most common value in a column, this is [None] if there are zero rows.
+    ///
+    /// If multiple values are the most common, then one of them is returned.
+    ///
+    /// ```
+    /// # use rust_query::{Table, aggregate, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Anna" }).unwrap();
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let first_letter = txn.query_one(aggregate(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.mode(user.name().starts_with("A"))
+    /// }));
+    /// assert_eq!(first_letter, Some(true));
+    /// ```
+    pub fn mode<T>(&self, val: impl IntoExpr<'inner, S, Typ = T>) -> Expr<'outer, S, Option<T>>
+    where
+        T: EqTyp + MyTyp<Sql: Nullable>,
+    {
+        let val = val.into_expr().inner.build_expr(self.ast.builder());
+        let field = self.ast.scope.new_field();
+        let count = self.ast.scope.new_field();
+
+        // count every value per group
+        let mut counts = self.query.ast.build_select(true);
+        counts.clear_selects();
+        for (group, alias) in self.ast.filter_on.iter() {
+            counts.expr_as(group.clone(), *alias);
+        }
+        counts.expr_as(val.clone(), field);
+        counts.add_group_by([val]);
+        counts.expr_as(sea_query::Expr::cust("COUNT(*)"), count);
+
+        // sqlite takes the bare value from the row with the maximum count
+        let mut select = SelectStatement::new();
+        select.from_subquery(counts, self.ast.scope.new_alias());
+        for (_, alias) in self.ast.filter_on.iter() {
+            select.column(*alias);
+            select.add_group_by([sea_query::Expr::col(*alias).into()]);
+        }
+        select.column(field);
+        select.expr(Func::max(sea_query::Expr::col(count)));
+
+        Expr::new(Aggr::<S, Option<T>> {
+            _p2: PhantomData,
+            select,
+            field,
+            conds: self.conds.clone(),
+        })
+    }
+
     /// Return the sum of a column.
     pub fn sum<T>(&self, val: impl IntoExpr<'inner, S, Typ = T>) -> Expr<'outer, S, T>
     where
@@ -116,12 +230,127 @@ impl<'outer, 'inner, S: 'static> Aggregate<'outer, 'inner, S> {
         })
     }
 
+    /// Return the number of rows.
+    pub fn count_all(&self) -> Expr<'outer, S, i64> {
+        let expr = sea_query::Expr::cust("COUNT(*)");
+        let val = self.select::<i64>(expr);
+        Expr::adhoc(move |b| {
+            sea_query::Expr::expr(val.build_expr(b))
+                .if_null(SimpleExpr::Constant(0i64.into_sea_value()))
+        })
+    }
+
+    /// Return the number of rows for which `cond` is true.
+    ///
+    /// ```
+    /// # use rust_query::{Table, aggregate};
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Anna" }).unwrap();
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let (a_names, total) = txn.query_one(aggregate(|rows| {
+    ///     let user = User::join(rows);
+    ///     (rows.count_true(user.name().starts_with("A")), rows.count_all())
+    /// }));
+    /// assert_eq!((a_names, total), (2, 3));
+    /// ```
+    pub fn count_true(&self, cond: impl IntoExpr<'inner, S, Typ = bool>) -> Expr<'outer, S, i64> {
+        let cond = cond.into_expr().inner;
+        let expr = Func::sum(cond.build_expr(self.ast.builder()));
+        let val = self.select::<i64>(expr);
+        Expr::adhoc(move |b| {
+            sea_query::Expr::expr(val.build_expr(b))
+                .if_null(SimpleExpr::Constant(0i64.into_sea_value()))
+        })
+    }
+
+    /// Return whether there are at least `min` rows.
+    ///
+    /// This is the same as `HAVING COUNT(*) >= min` in sql and can be used to
+    /// only include groups with a minimum number of members.
+    ///
+    /// ```
+    /// # use rust_query::{Table, aggregate};
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// let two_users = aggregate(|rows| {
+    ///     User::join(rows);
+    ///     rows.having_count(2)
+    /// });
+    /// assert!(!txn.query_one(two_users.clone()));
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// assert!(txn.query_one(two_users));
+    /// ```
+    pub fn having_count(&self, min: i64) -> Expr<'outer, S, bool> {
+        let expr = sea_query::Expr::cust("COUNT(*)");
+        let val = self.select::<i64>(expr);
+        Expr::adhoc(move |b| {
+            sea_query::Expr::expr(val.build_expr(b))
+                .if_null(SimpleExpr::Constant(0i64.into_sea_value()))
+                .gte(min)
+        })
+    }
+
     /// Return whether there are any rows.
     pub fn exists(&self) -> Expr<'outer, S, bool> {
         let expr = SimpleExpr::Constant(1.into_sea_value());
         let val = self.select::<i64>(expr);
         Expr::adhoc(move |b| sea_query::Expr::expr(val.build_expr(b)).is_not_null())
     }
+
+    /// Return whether `cond` is true for any of the rows.
+    ///
+    /// This is [false] if there are zero rows.
+    ///
+    /// ```
+    /// # use rust_query::{Table, aggregate};
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// let any_bob = aggregate(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.any(user.name().eq("Bob"))
+    /// });
+    /// assert!(!txn.query_one(any_bob.clone()));
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// assert!(txn.query_one(any_bob));
+    /// ```
+    pub fn any(&self, cond: impl IntoExpr<'inner, S, Typ = bool>) -> Expr<'outer, S, bool> {
+        let cond = cond.into_expr().inner;
+        let expr = Func::max(cond.build_expr(self.ast.builder()));
+        let val = self.select::<bool>(expr);
+        Expr::adhoc(move |b| {
+            sea_query::Expr::expr(val.build_expr(b)).if_null(SimpleExpr::Constant(false.into()))
+        })
+    }
+
+    /// Return whether `cond` is true for all of the rows.
+    ///
+    /// This is [true] if there are zero rows, just like [Iterator::all].
+    ///
+    /// ```
+    /// # use rust_query::{Table, aggregate};
+    /// # use rust_query::private::doctest::*;
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// let all_alice = aggregate(|rows| {
+    ///     let user = User::join(rows);
+    ///     rows.all(user.name().eq("Alice"))
+    /// });
+    /// assert!(txn.query_one(all_alice.clone()));
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// assert!(!txn.query_one(all_alice));
+    /// ```
+    pub fn all(&self, cond: impl IntoExpr<'inner, S, Typ = bool>) -> Expr<'outer, S, bool> {
+        let cond = cond.into_expr().inner;
+        let expr = Func::min(cond.build_expr(self.ast.builder()));
+        let val = self.select::<bool>(expr);
+        Expr::adhoc(move |b| {
+            sea_query::Expr::expr(val.build_expr(b)).if_null(SimpleExpr::Constant(true.into()))
+        })
+    }
 }
 
 pub struct Aggr<S, T> {
@@ -180,6 +409,29 @@ impl<S, T: Table> Deref for Aggr<S, T> {
 /// }));
 /// assert_eq!(res, 1, "there is one user in the database");
 /// ```
+///
+/// The result of an aggregate is a normal [Expr] in the outer query.
+/// This means that it can be used with [Rows::filter](crate::args::Rows::filter)
+/// to filter the outer rows on the aggregate, just like `HAVING` in sql.
+///
+/// ```
+/// # use rust_query::{Table, aggregate};
+/// # use rust_query::private::doctest::*;
+/// # let mut client = get_client();
+/// # let mut txn = get_txn(&mut client);
+/// txn.insert(User { name: "Bob" }).unwrap();
+/// let names = txn.query(|rows| {
+///     let user = User::join(rows);
+///     let same_name = aggregate(|rows| {
+///         let other = User::join(rows);
+///         rows.filter_on(other.name(), user.name());
+///         rows.count_distinct(other)
+///     });
+///     rows.filter(same_name.gt(0));
+///     rows.into_vec(user.name())
+/// });
+/// assert_eq!(names, ["Alice", "Bob"]);
+/// ```
 pub fn aggregate<'outer, S, F, R>(f: F) -> R
 where
     F: for<'inner> FnOnce(&mut Aggregate<'outer, 'inner, S>) -> R,
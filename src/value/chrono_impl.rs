@@ -0,0 +1,73 @@
+//! Timestamps from [chrono] are stored as unix seconds, the same as [crate::UnixEpoch].
+//! Sub-second precision is not stored.
+//! Dates and times are stored as text in the ISO 8601 format that sqlite uses.
+//! The text representation sorts the same as the values in rust, as long as
+//! the years are between 0 and 9999.
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use rust_query::{IntoExpr, LocalClient, Table, migration::{Config, schema}};
+//!
+//! #[schema(Schema)]
+//! pub mod vN {
+//!     pub struct Event {
+//!         pub name: String,
+//!         pub day: chrono::NaiveDate,
+//!     }
+//! }
+//! use v0::*;
+//!
+//! # fn main() {
+//! let mut client = LocalClient::try_new().unwrap();
+//! let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+//! let mut txn = client.transaction_mut(&db);
+//!
+//! let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+//! txn.insert(Event { name: "leap", day }).unwrap();
+//! let later = txn.query(|rows| {
+//!     let event = Event::join(rows);
+//!     rows.filter(event.day().gt(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()));
+//!     rows.into_vec(event.day())
+//! });
+//! assert_eq!(later, [day]);
+//!
+//! let first = chrono::DateTime::from_timestamp(1_000_000_000, 0).unwrap();
+//! let second = chrono::DateTime::from_timestamp(2_000_000_000, 0).unwrap();
+//! assert!(txn.query_one(first.into_expr().lt(second)));
+//! # }
+//! ```
+
+use ::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use super::OrdTyp;
+
+const DATE: &str = "%Y-%m-%d";
+const TIME: &str = "%H:%M:%S%.f";
+
+stored_as!(
+    DateTime<Utc> as i64,
+    |val| val.timestamp(),
+    |val| DateTime::from_timestamp(val, 0)
+);
+impl OrdTyp for DateTime<Utc> {}
+
+stored_as!(
+    NaiveDateTime as i64,
+    |val| val.and_utc().timestamp(),
+    |val| DateTime::from_timestamp(val, 0).map(|x| x.naive_utc())
+);
+impl OrdTyp for NaiveDateTime {}
+
+stored_as!(
+    NaiveDate as String,
+    |val| val.format(DATE).to_string(),
+    |val| NaiveDate::parse_from_str(&val, DATE).ok()
+);
+impl OrdTyp for NaiveDate {}
+
+stored_as!(
+    NaiveTime as String,
+    |val| val.format(TIME).to_string(),
+    |val| NaiveTime::parse_from_str(&val, TIME).ok()
+);
+impl OrdTyp for NaiveTime {}
@@ -0,0 +1,51 @@
+use std::rc::Rc;
+
+use super::{Expr, IntoExpr};
+
+/// A reusable column definition that is computed from a row of type `From`.
+///
+/// This is useful to select columns at runtime, for example by storing the
+/// definitions in a [HashMap](std::collections::HashMap) and looking them up by name.
+/// A [ComputedColumn] can be used in any number of queries.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use rust_query::{ComputedColumn, Table, private::doctest::*};
+/// # let mut client = get_client();
+/// # let txn = get_txn(&mut client);
+/// type UserColumn = ComputedColumn<Empty, User, String>;
+/// let columns = HashMap::from([
+///     ("name", UserColumn::new(|user| user.name())),
+///     ("shout", UserColumn::new(|user| user.name().upper())),
+/// ]);
+///
+/// let picked = &columns["shout"];
+/// let values = txn.query(|rows| {
+///     let user = User::join(rows);
+///     rows.into_vec(picked.apply(&user))
+/// });
+/// assert_eq!(values, ["ALICE"]);
+/// ```
+pub struct ComputedColumn<S, From, T> {
+    f: Rc<DynComputed<S, From, T>>,
+}
+
+type DynComputed<S, From, T> = dyn for<'a> Fn(Expr<'a, S, From>) -> Expr<'a, S, T>;
+
+impl<S, From, T> Clone for ComputedColumn<S, From, T> {
+    fn clone(&self) -> Self {
+        Self { f: self.f.clone() }
+    }
+}
+
+impl<S, From, T> ComputedColumn<S, From, T> {
+    /// Create a new [ComputedColumn] from a function that computes the column.
+    pub fn new(f: impl for<'a> Fn(Expr<'a, S, From>) -> Expr<'a, S, T> + 'static) -> Self {
+        Self { f: Rc::new(f) }
+    }
+
+    /// Compute the column for a row.
+    pub fn apply<'a>(&self, from: impl IntoExpr<'a, S, Typ = From>) -> Expr<'a, S, T> {
+        (self.f)(from.into_expr())
+    }
+}
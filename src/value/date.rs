@@ -0,0 +1,212 @@
+use sea_query::{Alias, SimpleExpr};
+
+use crate::{Expr, IntoExpr, value::Typed};
+
+fn strftime_private<'column, S>(
+    format: &str,
+    time: impl IntoExpr<'column, S, Typ = i64>,
+) -> impl 'static + Fn(crate::value::ValueBuilder) -> SimpleExpr {
+    let format = format.to_owned();
+    let time = time.into_expr().inner;
+    move |b| {
+        sea_query::Expr::cust_with_exprs(
+            "strftime(?, ?, 'unixepoch')",
+            [format.clone().into(), time.build_expr(b)],
+        )
+    }
+}
+
+/// Format a unix timestamp (in seconds) as text.
+///
+/// The `format` uses the same substitutions as the sqlite `strftime` function.
+///
+/// ```
+/// # use rust_query::date::strftime;
+/// # let mut client = rust_query::private::doctest::get_client();
+/// # let txn = rust_query::private::doctest::get_txn(&mut client);
+/// assert_eq!(txn.query_one(strftime("%Y-%m-%d", 1_000_000_000)), "2001-09-09");
+/// ```
+pub fn strftime<'column, S>(
+    format: &str,
+    time: impl IntoExpr<'column, S, Typ = i64>,
+) -> Expr<'column, S, String> {
+    Expr::adhoc(strftime_private(format, time))
+}
+
+fn strftime_int<'column, S>(
+    format: &str,
+    time: impl IntoExpr<'column, S, Typ = i64>,
+) -> Expr<'column, S, i64> {
+    let val = strftime_private(format, time);
+    Expr::adhoc(move |b| sea_query::Expr::expr(val(b)).cast_as(Alias::new("integer")))
+}
+
+/// Retrieve the year of a unix timestamp (in seconds).
+///
+/// ```
+/// # use rust_query::date::year;
+/// # let mut client = rust_query::private::doctest::get_client();
+/// # let txn = rust_query::private::doctest::get_txn(&mut client);
+/// assert_eq!(txn.query_one(year(1_000_000_000)), 2001);
+/// ```
+pub fn year<'column, S>(time: impl IntoExpr<'column, S, Typ = i64>) -> Expr<'column, S, i64> {
+    strftime_int("%Y", time)
+}
+
+/// Retrieve the month of a unix timestamp (in seconds), from 1 to 12.
+///
+/// ```
+/// # use rust_query::date::month;
+/// # let mut client = rust_query::private::doctest::get_client();
+/// # let txn = rust_query::private::doctest::get_txn(&mut client);
+/// assert_eq!(txn.query_one(month(1_000_000_000)), 9);
+/// ```
+pub fn month<'column, S>(time: impl IntoExpr<'column, S, Typ = i64>) -> Expr<'column, S, i64> {
+    strftime_int("%m", time)
+}
+
+/// Retrieve the day of the week of a unix timestamp (in seconds).
+///
+/// The result is from 0 to 6, where 0 is sunday.
+///
+/// ```
+/// # use rust_query::date::day_of_week;
+/// # let mut client = rust_query::private::doctest::get_client();
+/// # let txn = rust_query::private::doctest::get_txn(&mut client);
+/// assert_eq!(txn.query_one(day_of_week(1_000_000_000)), 0);
+/// ```
+pub fn day_of_week<'column, S>(
+    time: impl IntoExpr<'column, S, Typ = i64>,
+) -> Expr<'column, S, i64> {
+    strftime_int("%w", time)
+}
+
+/// Units of time that are used by [date_trunc].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    /// Weeks start on monday.
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+/// Round a unix timestamp (in seconds) down to the start of the `unit` it is in.
+///
+/// The result is also a unix timestamp, this makes it useful for grouping by time period.
+///
+/// ```
+/// # use rust_query::date::{DateUnit, date_trunc};
+/// # let mut client = rust_query::private::doctest::get_client();
+/// # let txn = rust_query::private::doctest::get_txn(&mut client);
+/// // 2001-09-09 01:46:40
+/// let time = 1_000_000_000;
+/// // 2001-09-01 00:00:00
+/// assert_eq!(txn.query_one(date_trunc(DateUnit::Month, time)), 999_302_400);
+/// // 2001-09-03 00:00:00 (a monday)
+/// assert_eq!(txn.query_one(date_trunc(DateUnit::Week, time)), 999_475_200);
+/// // 2001-07-01 00:00:00
+/// assert_eq!(txn.query_one(date_trunc(DateUnit::Quarter, time)), 993_945_600);
+/// ```
+pub fn date_trunc<'column, S>(
+    unit: DateUnit,
+    time: impl IntoExpr<'column, S, Typ = i64>,
+) -> Expr<'column, S, i64> {
+    let time = time.into_expr().inner;
+    let sql = match unit {
+        DateUnit::Second => "unixepoch(?, 'unixepoch')",
+        DateUnit::Minute => "unixepoch(strftime('%Y-%m-%d %H:%M:00', ?, 'unixepoch'))",
+        DateUnit::Hour => "unixepoch(strftime('%Y-%m-%d %H:00:00', ?, 'unixepoch'))",
+        DateUnit::Day => "unixepoch(?, 'unixepoch', 'start of day')",
+        DateUnit::Week => "unixepoch(?, 'unixepoch', 'start of day', '-6 days', 'weekday 1')",
+        DateUnit::Month => "unixepoch(?, 'unixepoch', 'start of month')",
+        DateUnit::Quarter => {
+            "unixepoch(?, 'unixepoch', 'start of month', \
+            '-' || ((strftime('%m', ?, 'unixepoch') - 1) % 3) || ' months')"
+        }
+        DateUnit::Year => "unixepoch(?, 'unixepoch', 'start of year')",
+    };
+    Expr::adhoc(move |b| {
+        // the quarter needs the time twice, other units ignore the second value
+        let time = time.build_expr(b);
+        sea_query::Expr::cust_with_exprs(sql, [time.clone(), time])
+    })
+}
+
+/// Calculate the number of whole `unit`s from `start` to `end`, both unix timestamps (in seconds).
+///
+/// The result is negative if `end` is before `start`.
+/// Months, quarters and years are calculated using the calendar, so the difference between
+/// `2001-01-31` and `2001-02-28` is zero months, but the difference between `2001-01-28`
+/// and `2001-02-28` is one month.
+///
+/// ```
+/// # use rust_query::date::{DateUnit, date_diff};
+/// # let mut client = rust_query::private::doctest::get_client();
+/// # let txn = rust_query::private::doctest::get_txn(&mut client);
+/// // 2001-09-09 01:46:40
+/// let start = 1_000_000_000;
+/// // 2001-12-09 01:46:39
+/// let end = 1_007_862_399;
+/// assert_eq!(txn.query_one(date_diff(DateUnit::Day, start, end)), 90);
+/// assert_eq!(txn.query_one(date_diff(DateUnit::Month, start, end)), 2);
+/// assert_eq!(txn.query_one(date_diff(DateUnit::Month, start, end + 1)), 3);
+/// assert_eq!(txn.query_one(date_diff(DateUnit::Month, end + 1, start)), -3);
+/// ```
+pub fn date_diff<'column, S>(
+    unit: DateUnit,
+    start: impl IntoExpr<'column, S, Typ = i64>,
+    end: impl IntoExpr<'column, S, Typ = i64>,
+) -> Expr<'column, S, i64> {
+    let start = start.into_expr().inner;
+    let end = end.into_expr().inner;
+    Expr::adhoc(move |b| {
+        let start = start.build_expr(b);
+        let end = end.build_expr(b);
+        let seconds = || end.clone().sub(start.clone());
+        match unit {
+            DateUnit::Second => seconds(),
+            DateUnit::Minute => seconds().div(60),
+            DateUnit::Hour => seconds().div(60 * 60),
+            DateUnit::Day => seconds().div(24 * 60 * 60),
+            DateUnit::Week => seconds().div(7 * 24 * 60 * 60),
+            DateUnit::Month => month_diff(&start, &end),
+            DateUnit::Quarter => month_diff(&start, &end).div(3),
+            DateUnit::Year => month_diff(&start, &end).div(12),
+        }
+    })
+}
+
+fn month_diff(start: &SimpleExpr, end: &SimpleExpr) -> SimpleExpr {
+    let part = |format: &str, time: &SimpleExpr| {
+        sea_query::Expr::cust_with_exprs(
+            format!("strftime('{format}', ?, 'unixepoch')"),
+            [time.clone()],
+        )
+    };
+    let months = |time: &SimpleExpr| {
+        let year = part("%Y", time).cast_as(Alias::new("integer"));
+        let month = part("%m", time).cast_as(Alias::new("integer"));
+        year.mul(12).add(month)
+    };
+    let diff = months(end).sub(months(start));
+    // the last month is not complete if the day and time of `end` is earlier than `start`
+    let rest_start = part("%d %H:%M:%S", start);
+    let rest_end = part("%d %H:%M:%S", end);
+    let correction = sea_query::Expr::cust_with_exprs(
+        "((? > 0 AND ? < ?) - (? < 0 AND ? > ?))",
+        [
+            diff.clone(),
+            rest_end.clone(),
+            rest_start.clone(),
+            diff.clone(),
+            rest_end,
+            rest_start,
+        ],
+    );
+    diff.sub(correction)
+}
@@ -1,10 +1,14 @@
-use sea_query::{Alias, ExprTrait, extension::sqlite::SqliteExpr};
+use sea_query::{Alias, ExprTrait, Func, Nullable, SimpleExpr, extension::sqlite::SqliteExpr};
 
-use super::{EqTyp, Expr, IntoExpr, NumTyp, Typed};
+use crate::{IntoSelectExt, Select};
+
+use super::{EqTyp, Expr, IntoExpr, MyTyp, NumTyp, Numeric, OrdTyp, Textual, Typed};
 
 impl<'column, S, T: NumTyp> Expr<'column, S, T> {
     /// Add two expressions together.
     ///
+    /// Adding an [i64] and an [f64] results in an [f64].
+    ///
     /// ```
     /// # use rust_query::IntoExpr;
     /// # let mut client = rust_query::private::doctest::get_client();
@@ -12,7 +16,13 @@ impl<'column, S, T: NumTyp> Expr<'column, S, T> {
     /// assert_eq!(txn.query_one(1.into_expr().add(2)), 3);
     /// assert_eq!(txn.query_one(1.0.into_expr().add(2.0)), 3.0);
     /// ```
-    pub fn add(&self, rhs: impl IntoExpr<'column, S, Typ = T>) -> Expr<'column, S, T> {
+    pub fn add<R: NumTyp>(
+        &self,
+        rhs: impl IntoExpr<'column, S, Typ = R>,
+    ) -> Expr<'column, S, T::Output>
+    where
+        T: Numeric<R>,
+    {
         let lhs = self.inner.clone();
         let rhs = rhs.into_expr().inner;
         Expr::adhoc(move |b| lhs.build_expr(b).add(rhs.build_expr(b)))
@@ -27,7 +37,13 @@ impl<'column, S, T: NumTyp> Expr<'column, S, T> {
     /// assert_eq!(txn.query_one(1.into_expr().sub(2)), -1);
     /// assert_eq!(txn.query_one(1.0.into_expr().sub(2.0)), -1.0);
     /// ```
-    pub fn sub(&self, rhs: impl IntoExpr<'column, S, Typ = T>) -> Expr<'column, S, T> {
+    pub fn sub<R: NumTyp>(
+        &self,
+        rhs: impl IntoExpr<'column, S, Typ = R>,
+    ) -> Expr<'column, S, T::Output>
+    where
+        T: Numeric<R>,
+    {
         let lhs = self.inner.clone();
         let rhs = rhs.into_expr().inner;
         Expr::adhoc(move |b| lhs.build_expr(b).sub(rhs.build_expr(b)))
@@ -41,13 +57,47 @@ impl<'column, S, T: NumTyp> Expr<'column, S, T> {
     /// # let txn = rust_query::private::doctest::get_txn(&mut client);
     /// assert_eq!(txn.query_one(2.into_expr().mul(3)), 6);
     /// assert_eq!(txn.query_one(2.0.into_expr().mul(3.0)), 6.0);
+    /// assert_eq!(txn.query_one(2.into_expr().mul(1.5)), 3.0);
     /// ```
-    pub fn mul(&self, rhs: impl IntoExpr<'column, S, Typ = T>) -> Expr<'column, S, T> {
+    pub fn mul<R: NumTyp>(
+        &self,
+        rhs: impl IntoExpr<'column, S, Typ = R>,
+    ) -> Expr<'column, S, T::Output>
+    where
+        T: Numeric<R>,
+    {
         let lhs = self.inner.clone();
         let rhs = rhs.into_expr().inner;
         Expr::adhoc(move |b| lhs.build_expr(b).mul(rhs.build_expr(b)))
     }
 
+    /// Divide one expression by another.
+    ///
+    /// Dividing two [i64] values rounds the result towards zero, like in rust.
+    /// Dividing by zero results in [None].
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(7.into_expr().div(2)), Some(3));
+    /// assert_eq!(txn.query_one(7.into_expr().div(2.0)), Some(3.5));
+    /// assert_eq!(txn.query_one(7.into_expr().div(0)), None);
+    /// ```
+    pub fn div<R: NumTyp>(
+        &self,
+        rhs: impl IntoExpr<'column, S, Typ = R>,
+    ) -> Expr<'column, S, Option<T::Output>>
+    where
+        T: Numeric<R>,
+    {
+        let lhs = self.inner.clone();
+        let rhs = rhs.into_expr().inner;
+        Expr::adhoc(move |b| lhs.build_expr(b).div(rhs.build_expr(b)))
+    }
+}
+
+impl<'column, S, T: OrdTyp> Expr<'column, S, T> {
     /// Compute the less than operator (<) of two expressions.
     ///
     /// ```
@@ -178,6 +228,61 @@ impl<'column, S> Expr<'column, S, bool> {
         let rhs = rhs.into_expr().inner;
         Expr::adhoc(move |b| lhs.build_expr(b).or(rhs.build_expr(b)))
     }
+
+    /// Use the first expression if this expression is true, otherwise use the second expression.
+    ///
+    /// This is the same as `CASE WHEN cond THEN a ELSE b END` in sql.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(true.into_expr().if_then_else(1, 2)), 1);
+    /// assert_eq!(txn.query_one(false.into_expr().if_then_else("yes", "no")), "no");
+    ///
+    /// let small = 3.into_expr().lt(5);
+    /// assert_eq!(txn.query_one(small.if_then_else(Some(3), None::<i64>)), Some(3));
+    /// ```
+    pub fn if_then_else<T: 'static>(
+        &self,
+        then_val: impl IntoExpr<'column, S, Typ = T>,
+        else_val: impl IntoExpr<'column, S, Typ = T>,
+    ) -> Expr<'column, S, T> {
+        let cond = self.inner.clone();
+        let then_val = then_val.into_expr().inner;
+        let else_val = else_val.into_expr().inner;
+        Expr::adhoc(move |b| {
+            sea_query::Expr::case(cond.build_expr(b), then_val.build_expr(b))
+                .finally(else_val.build_expr(b))
+                .into()
+        })
+    }
+
+    /// Use the expression if this expression is true, otherwise the result is [None].
+    ///
+    /// This is the same as [bool::then_some] in rust.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(true.into_expr().then_some(1)), Some(1));
+    /// assert_eq!(txn.query_one(false.into_expr().then_some("yes")), None);
+    /// ```
+    pub fn then_some<T: MyTyp<Sql: Nullable>>(
+        &self,
+        val: impl IntoExpr<'column, S, Typ = T>,
+    ) -> Expr<'column, S, Option<T>> {
+        const NULL: SimpleExpr = SimpleExpr::Keyword(sea_query::Keyword::Null);
+
+        let cond = self.inner.clone();
+        let val = val.into_expr().inner;
+        Expr::adhoc(move |b| {
+            sea_query::Expr::case(cond.build_expr(b), val.build_expr(b))
+                .finally(NULL)
+                .into()
+        })
+    }
 }
 
 impl<'column, S, Typ: 'static> Expr<'column, S, Option<Typ>> {
@@ -241,9 +346,151 @@ impl<'column, S> Expr<'column, S, i64> {
         let val = self.inner.clone();
         Expr::adhoc(move |b| val.build_expr(b).cast_as(Alias::new("real")))
     }
+
+    /// Compute the bitwise and (&) of two expressions.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(0b1100.into_expr().bit_and(0b1010)), 0b1000);
+    /// ```
+    pub fn bit_and(&self, rhs: impl IntoExpr<'column, S, Typ = i64>) -> Expr<'column, S, i64> {
+        let lhs = self.inner.clone();
+        let rhs = rhs.into_expr().inner;
+        Expr::adhoc(move |b| lhs.build_expr(b).bit_and(rhs.build_expr(b)))
+    }
+
+    /// Compute the bitwise or (|) of two expressions.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(0b1100.into_expr().bit_or(0b1010)), 0b1110);
+    /// ```
+    pub fn bit_or(&self, rhs: impl IntoExpr<'column, S, Typ = i64>) -> Expr<'column, S, i64> {
+        let lhs = self.inner.clone();
+        let rhs = rhs.into_expr().inner;
+        Expr::adhoc(move |b| lhs.build_expr(b).bit_or(rhs.build_expr(b)))
+    }
+
+    /// Compute the bitwise exclusive or (^) of two expressions.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(0b1100.into_expr().bit_xor(0b1010)), 0b0110);
+    /// assert_eq!(txn.query_one((-1).into_expr().bit_xor(i64::MIN)), i64::MAX);
+    /// ```
+    pub fn bit_xor(&self, rhs: impl IntoExpr<'column, S, Typ = i64>) -> Expr<'column, S, i64> {
+        let lhs = self.inner.clone();
+        let rhs = rhs.into_expr().inner;
+        // sqlite does not have an xor operator
+        Expr::adhoc(move |b| {
+            let (lhs, rhs) = (lhs.build_expr(b), rhs.build_expr(b));
+            sea_query::Expr::cust_with_exprs(
+                "(? | ?) & ~(? & ?)",
+                [lhs.clone(), rhs.clone(), lhs, rhs],
+            )
+        })
+    }
+
+    /// Compute the bitwise not (~) of the expression.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(0.into_expr().bit_not()), -1);
+    /// ```
+    pub fn bit_not(&self) -> Expr<'column, S, i64> {
+        let val = self.inner.clone();
+        Expr::adhoc(move |b| sea_query::Expr::cust_with_exprs("~?", [val.build_expr(b)]))
+    }
 }
 
-impl<'column, S> Expr<'column, S, String> {
+impl<'column, S> Expr<'column, S, f64> {
+    /// Raise the expression to the power of the exponent.
+    ///
+    /// The result is [None] if it is not a number, for example when taking the root of a negative number.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(2.0.into_expr().power(3.0)), Some(8.0));
+    /// assert_eq!(txn.query_one((-8.0).into_expr().power(0.5)), None);
+    /// ```
+    pub fn power(
+        &self,
+        exponent: impl IntoExpr<'column, S, Typ = f64>,
+    ) -> Expr<'column, S, Option<f64>> {
+        let val = self.inner.clone();
+        let exponent = exponent.into_expr().inner;
+        Expr::adhoc(move |b| {
+            Func::cust(Alias::new("pow"))
+                .arg(val.build_expr(b))
+                .arg(exponent.build_expr(b))
+                .into()
+        })
+    }
+
+    /// Calculate the square root of the expression.
+    ///
+    /// The result is [None] for negative numbers.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(16.0.into_expr().sqrt()), Some(4.0));
+    /// assert_eq!(txn.query_one((-1.0).into_expr().sqrt()), None);
+    /// ```
+    pub fn sqrt(&self) -> Expr<'column, S, Option<f64>> {
+        let val = self.inner.clone();
+        Expr::adhoc(move |b| Func::cust(Alias::new("sqrt")).arg(val.build_expr(b)).into())
+    }
+
+    /// Calculate the logarithm of the expression with the given base.
+    ///
+    /// The result is [None] if the expression is not positive.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(100.0.into_expr().log(10.0)), Some(2.0));
+    /// assert_eq!(txn.query_one(0.0.into_expr().log(10.0)), None);
+    /// ```
+    pub fn log(&self, base: f64) -> Expr<'column, S, Option<f64>> {
+        let val = self.inner.clone();
+        Expr::adhoc(move |b| {
+            Func::cust(Alias::new("log"))
+                .arg(base)
+                .arg(val.build_expr(b))
+                .into()
+        })
+    }
+
+    /// Calculate `e` raised to the power of the expression.
+    ///
+    /// The result is [None] if the expression is not a number.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one(0.0.into_expr().exp()), Some(1.0));
+    /// ```
+    pub fn exp(&self) -> Expr<'column, S, Option<f64>> {
+        let val = self.inner.clone();
+        Expr::adhoc(move |b| Func::cust(Alias::new("exp")).arg(val.build_expr(b)).into())
+    }
+}
+
+impl<'column, S, T: Textual> Expr<'column, S, T> {
     /// Check if the expression starts with the string pattern.
     ///
     /// Matches case-sensitive. The pattern gets automatically escaped.
@@ -254,8 +501,10 @@ impl<'column, S> Expr<'column, S, String> {
     /// # let txn = rust_query::private::doctest::get_txn(&mut client);
     /// assert_eq!(txn.query_one("hello world".into_expr().starts_with("hello")), true);
     /// assert_eq!(txn.query_one("hello world".into_expr().starts_with("Hello")), false);
+    /// assert_eq!(txn.query_one(Some("hello").into_expr().starts_with("hello")), Some(true));
+    /// assert_eq!(txn.query_one(None::<String>.into_expr().starts_with("hello")), None);
     /// ```
-    pub fn starts_with(&self, pattern: impl AsRef<str>) -> Expr<'column, S, bool> {
+    pub fn starts_with(&self, pattern: impl AsRef<str>) -> Expr<'column, S, T::Bool> {
         self.glob(format!("{}*", escape_glob(pattern)))
     }
 
@@ -270,7 +519,7 @@ impl<'column, S> Expr<'column, S, String> {
     /// assert_eq!(txn.query_one("hello world".into_expr().ends_with("world")), true);
     /// assert_eq!(txn.query_one("hello world".into_expr().ends_with("World")), false);
     /// ```
-    pub fn ends_with(&self, pattern: impl AsRef<str>) -> Expr<'column, S, bool> {
+    pub fn ends_with(&self, pattern: impl AsRef<str>) -> Expr<'column, S, T::Bool> {
         self.glob(format!("*{}", escape_glob(pattern)))
     }
 
@@ -285,7 +534,7 @@ impl<'column, S> Expr<'column, S, String> {
     /// assert_eq!(txn.query_one("rhubarb".into_expr().contains("bar")), true);
     /// assert_eq!(txn.query_one("rhubarb".into_expr().contains("Bar")), false);
     /// ```
-    pub fn contains(&self, pattern: impl AsRef<str>) -> Expr<'column, S, bool> {
+    pub fn contains(&self, pattern: impl AsRef<str>) -> Expr<'column, S, T::Bool> {
         self.glob(format!("*{}*", escape_glob(pattern)))
     }
 
@@ -302,7 +551,7 @@ impl<'column, S> Expr<'column, S, String> {
     /// assert_eq!(txn.query_one("hello world".into_expr().glob("?ello*")), true);
     /// assert_eq!(txn.query_one("hello world".into_expr().glob("Hell*")), false);
     /// ```
-    pub fn glob(&self, rhs: impl IntoExpr<'column, S, Typ = String>) -> Expr<'column, S, bool> {
+    pub fn glob(&self, rhs: impl IntoExpr<'column, S, Typ = String>) -> Expr<'column, S, T::Bool> {
         let lhs = self.inner.clone();
         let rhs = rhs.into_expr().inner;
         Expr::adhoc(move |b| sea_query::Expr::expr(lhs.build_expr(b)).glob(rhs.build_expr(b)))
@@ -321,7 +570,7 @@ impl<'column, S> Expr<'column, S, String> {
     /// assert_eq!(txn.query_one("hello world".into_expr().like("HELLO%")), true);
     /// assert_eq!(txn.query_one("hello world".into_expr().like("he_o%")), false);
     /// ```
-    pub fn like(&self, pattern: impl Into<String>) -> Expr<'column, S, bool> {
+    pub fn like(&self, pattern: impl Into<String>) -> Expr<'column, S, T::Bool> {
         let lhs = self.inner.clone();
         let rhs = pattern.into();
         Expr::adhoc(move |b| {
@@ -329,6 +578,93 @@ impl<'column, S> Expr<'column, S, String> {
                 .like(sea_query::LikeExpr::new(&rhs).escape('\\'))
         })
     }
+
+    /// Check if the expression matches the regular expression `pattern`.
+    ///
+    /// This uses the sqlite `REGEXP` operator, which requires a `regexp` function to be
+    /// registered with [crate::migration::Config::regexp]. Queries using this method panic otherwise.
+    /// The syntax of `pattern` depends on the engine that was registered.
+    ///
+    /// See [crate::migration::Config::regexp] for an example.
+    pub fn matches_regex(&self, pattern: impl AsRef<str>) -> Expr<'column, S, T::Bool> {
+        let val = self.inner.clone();
+        let pattern = pattern.as_ref().to_owned();
+        Expr::adhoc(move |b| {
+            sea_query::Expr::cust_with_exprs(
+                "? REGEXP ?",
+                [val.build_expr(b), pattern.clone().into()],
+            )
+        })
+    }
+
+    /// Convert ASCII characters to lowercase in sqlite.
+    ///
+    /// Sqlite only converts ASCII characters, all other characters are unchanged.
+    /// If you need full unicode support and don't need to use the result in the query,
+    /// then use [Expr::to_lowercase] instead.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one("Hello ÄÖ".into_expr().lower()), "hello ÄÖ");
+    /// assert_eq!(txn.query_one(Some("Hello").into_expr().lower()).as_deref(), Some("hello"));
+    /// ```
+    pub fn lower(&self) -> Expr<'column, S, T::Text> {
+        let val = self.inner.clone();
+        Expr::adhoc(move |b| sea_query::Func::lower(val.build_expr(b)).into())
+    }
+
+    /// Convert ASCII characters to uppercase in sqlite.
+    ///
+    /// Sqlite only converts ASCII characters, all other characters are unchanged.
+    /// If you need full unicode support and don't need to use the result in the query,
+    /// then use [Expr::to_uppercase] instead.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one("Hello äö".into_expr().upper()), "HELLO äö");
+    /// ```
+    pub fn upper(&self) -> Expr<'column, S, T::Text> {
+        let val = self.inner.clone();
+        Expr::adhoc(move |b| sea_query::Func::upper(val.build_expr(b)).into())
+    }
+}
+
+impl<'column, S> Expr<'column, S, String> {
+    /// Retrieve the string and convert it to lowercase in rust using [str::to_lowercase].
+    ///
+    /// In contrast to [Expr::lower], this handles all unicode characters.
+    /// The conversion happens after the query is executed, which is why this returns a [Select]
+    /// that can not be used in filters or other expressions.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one("Hello ÄÖ".into_expr().to_lowercase()), "hello äö");
+    /// ```
+    pub fn to_lowercase<'transaction>(&self) -> Select<'column, 'transaction, S, String> {
+        self.map_select(|val| val.to_lowercase())
+    }
+
+    /// Retrieve the string and convert it to uppercase in rust using [str::to_uppercase].
+    ///
+    /// In contrast to [Expr::upper], this handles all unicode characters.
+    /// The conversion happens after the query is executed, which is why this returns a [Select]
+    /// that can not be used in filters or other expressions.
+    ///
+    /// ```
+    /// # use rust_query::IntoExpr;
+    /// # let mut client = rust_query::private::doctest::get_client();
+    /// # let txn = rust_query::private::doctest::get_txn(&mut client);
+    /// assert_eq!(txn.query_one("Hello äö".into_expr().to_uppercase()), "HELLO ÄÖ");
+    /// ```
+    pub fn to_uppercase<'transaction>(&self) -> Select<'column, 'transaction, S, String> {
+        self.map_select(|val| val.to_uppercase())
+    }
 }
 
 // This is a copy of the function from the glob crate https://github.com/rust-lang/glob/blob/49ee1e92bd6e8c5854c0b339634f9b4b733aba4f/src/lib.rs#L720-L737.
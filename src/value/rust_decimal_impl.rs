@@ -0,0 +1,46 @@
+//! A [Decimal] is stored as text, so that no precision is lost.
+//! The value is normalized before it is stored, this makes equality checks in sqlite
+//! work as expected.
+//!
+//! Decimals can not be compared with `<` and `>` or used in sqlite arithmetic,
+//! because sqlite would convert the text to a float.
+//! To calculate a sum or average without losing precision, retrieve the values
+//! and aggregate them in rust instead.
+//! This is slower than aggregating in sqlite, but the result is exact.
+//!
+//! ```
+//! use rust_decimal::Decimal;
+//! use rust_query::{LocalClient, Table, migration::{Config, schema}};
+//!
+//! #[schema(Schema)]
+//! pub mod vN {
+//!     pub struct Payment {
+//!         pub amount: rust_decimal::Decimal,
+//!     }
+//! }
+//! use v0::*;
+//!
+//! # fn main() {
+//! let mut client = LocalClient::try_new().unwrap();
+//! let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+//! let mut txn = client.transaction_mut(&db);
+//!
+//! for amount in [Decimal::new(10, 2), Decimal::new(20, 2)] {
+//!     txn.insert(Payment { amount }).unwrap();
+//! }
+//! let amounts = txn.query(|rows| {
+//!     let payment = Payment::join(rows);
+//!     rows.into_vec(payment.amount())
+//! });
+//! let total: Decimal = amounts.into_iter().sum();
+//! assert_eq!(total, Decimal::new(3, 1));
+//! # }
+//! ```
+
+use ::rust_decimal::Decimal;
+
+stored_as!(
+    Decimal as String,
+    |val| val.normalize().to_string(),
+    |val| val.parse().ok()
+);
@@ -0,0 +1,70 @@
+/// Implement all traits that are needed to use `$typ` as a column type.
+///
+/// The value is stored in sqlite as `$sql`, which must be one of the basic column types.
+/// `$to_sql` converts a `&$typ` to `$sql` and `$from_sql` converts it back,
+/// the latter returns [None] if the value in the database is not valid.
+///
+/// Use `from_value = ...` instead of `$from_sql` to read the raw sqlite value,
+/// this allows reading values that are stored with a different type.
+#[allow(unused_macros)]
+macro_rules! stored_as {
+    ($typ:ty as $sql:ty, $to_sql:expr, from_value = $from_value:expr) => {
+        impl $crate::value::Typed for $typ {
+            type Typ = $typ;
+            fn build_expr(&self, b: $crate::value::ValueBuilder) -> ::sea_query::SimpleExpr {
+                let to_sql: fn(&$typ) -> $sql = $to_sql;
+                $crate::value::Typed::build_expr(&to_sql(self), b)
+            }
+        }
+
+        impl<'column, S> $crate::IntoExpr<'column, S> for $typ {
+            type Typ = $typ;
+            fn into_expr(self) -> $crate::Expr<'column, S, Self::Typ> {
+                $crate::Expr::new(self)
+            }
+        }
+
+        impl $crate::value::MyTyp for $typ {
+            type Prev = Self;
+            const TYP: $crate::hash::ColumnType = <$sql as $crate::value::MyTyp>::TYP;
+            type Out<'t> = Self;
+            type Sql = $sql;
+        }
+
+        impl $crate::value::SecretFromSql<'_> for $typ {
+            fn from_sql(
+                value: ::rusqlite::types::ValueRef<'_>,
+            ) -> ::rusqlite::types::FromSqlResult<Self> {
+                let from_value: fn(
+                    ::rusqlite::types::ValueRef<'_>,
+                ) -> ::rusqlite::types::FromSqlResult<$typ> = $from_value;
+                from_value(value)
+            }
+        }
+
+        impl $crate::value::EqTyp for $typ {}
+
+        impl<'transaction, S> $crate::FromExpr<'transaction, S, $typ> for $typ {
+            fn from_expr<'columns>(
+                col: impl $crate::IntoExpr<'columns, S, Typ = $typ>,
+            ) -> $crate::Select<'columns, 'transaction, S, Self> {
+                $crate::IntoSelect::into_select($crate::IntoExpr::into_expr(col))
+            }
+        }
+
+        impl<S> $crate::hash::SchemaType<S> for $typ {
+            type N = $crate::hash::NotNull;
+        }
+    };
+    ($typ:ty as $sql:ty, $to_sql:expr, $from_sql:expr) => {
+        stored_as!(
+            $typ as $sql,
+            $to_sql,
+            from_value = |value| {
+                let from_sql: fn($sql) -> Option<$typ> = $from_sql;
+                let value = <$sql as $crate::value::SecretFromSql>::from_sql(value)?;
+                from_sql(value).ok_or(::rusqlite::types::FromSqlError::InvalidType)
+            }
+        );
+    };
+}
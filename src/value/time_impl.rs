@@ -0,0 +1,104 @@
+//! Types from [time] use the same representation as the `chrono` feature.
+//! Timestamps are stored as unix seconds, the same as [crate::UnixEpoch],
+//! and durations are stored as a number of seconds.
+//! Sub-second precision is not stored for timestamps and durations.
+//! Dates and times are stored as text in the ISO 8601 format that sqlite uses.
+//!
+//! ```
+//! use rust_query::{IntoExpr, LocalClient, Table, migration::{Config, schema}};
+//! use time::{Date, Duration, Month, OffsetDateTime};
+//!
+//! #[schema(Schema)]
+//! pub mod vN {
+//!     pub struct Event {
+//!         pub day: time::Date,
+//!         pub length: time::Duration,
+//!     }
+//! }
+//! use v0::*;
+//!
+//! # fn main() {
+//! let mut client = LocalClient::try_new().unwrap();
+//! let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+//! let mut txn = client.transaction_mut(&db);
+//!
+//! let day = Date::from_calendar_date(2024, Month::February, 29).unwrap();
+//! let length = Duration::hours(2);
+//! txn.insert(Event { day, length }).unwrap();
+//! let long = txn.query(|rows| {
+//!     let event = Event::join(rows);
+//!     rows.filter(event.length().gt(Duration::hours(1)));
+//!     rows.into_vec((event.day(), event.length()))
+//! });
+//! assert_eq!(long, [(day, length)]);
+//!
+//! let first = OffsetDateTime::from_unix_timestamp(1_000_000_000).unwrap();
+//! let second = OffsetDateTime::from_unix_timestamp(2_000_000_000).unwrap();
+//! assert!(txn.query_one(first.into_expr().lt(second)));
+//! # }
+//! ```
+
+use ::time::{Date, Duration, Month, OffsetDateTime, Time};
+
+use super::OrdTyp;
+
+stored_as!(OffsetDateTime as i64, |val| val.unix_timestamp(), |val| {
+    OffsetDateTime::from_unix_timestamp(val).ok()
+});
+impl OrdTyp for OffsetDateTime {}
+
+stored_as!(
+    Date as String,
+    |val| format!(
+        "{:04}-{:02}-{:02}",
+        val.year(),
+        val.month() as u8,
+        val.day()
+    ),
+    |val| {
+        let mut parts = val.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Date::from_calendar_date(year, month, day).ok()
+    }
+);
+impl OrdTyp for Date {}
+
+stored_as!(
+    Time as String,
+    |val| {
+        let mut out = format!("{:02}:{:02}:{:02}", val.hour(), val.minute(), val.second());
+        // use the same number of digits as `chrono` does
+        let nanos = val.nanosecond();
+        if nanos % 1_000_000 == 0 && nanos != 0 {
+            out.push_str(&format!(".{:03}", nanos / 1_000_000));
+        } else if nanos % 1_000 == 0 && nanos != 0 {
+            out.push_str(&format!(".{:06}", nanos / 1_000));
+        } else if nanos != 0 {
+            out.push_str(&format!(".{nanos:09}"));
+        }
+        out
+    },
+    |val| {
+        let (hms, frac) = val.split_once('.').unwrap_or((&val, ""));
+        let mut parts = hms.splitn(3, ':');
+        let hour = parts.next()?.parse().ok()?;
+        let minute = parts.next()?.parse().ok()?;
+        let second = parts.next()?.parse().ok()?;
+        let mut nanos = 0;
+        if !frac.is_empty() {
+            if frac.len() > 9 {
+                return None;
+            }
+            nanos = format!("{frac:0<9}").parse().ok()?;
+        }
+        Time::from_hms_nano(hour, minute, second, nanos).ok()
+    }
+);
+impl OrdTyp for Time {}
+
+stored_as!(Duration as i64, |val| val.whole_seconds(), |val| Some(
+    Duration::seconds(val)
+));
+impl OrdTyp for Duration {}
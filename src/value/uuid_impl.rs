@@ -0,0 +1,49 @@
+//! A [Uuid] is stored as a blob of 16 bytes.
+//! Reading a [Uuid] that was stored as text is also supported.
+//!
+//! There is no attribute to use a [Uuid] as primary key, every table keeps its integer `id`.
+//! A `#[unique]` [Uuid] column can be used to look up rows instead.
+//!
+//! ```
+//! use rust_query::{LocalClient, Table, migration::{Config, schema}};
+//! use uuid::Uuid;
+//!
+//! #[schema(Schema)]
+//! pub mod vN {
+//!     pub struct Session {
+//!         #[unique]
+//!         pub key: uuid::Uuid,
+//!     }
+//! }
+//! use v0::*;
+//!
+//! # fn main() {
+//! let mut client = LocalClient::try_new().unwrap();
+//! let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+//! let mut txn = client.transaction_mut(&db);
+//!
+//! let key = Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8);
+//! let session = txn.insert(Session { key }).unwrap();
+//! assert_eq!(txn.query_one(Session::unique(key)), Some(session));
+//! assert_eq!(txn.query_one(session.key()), key);
+//! # }
+//! ```
+
+use ::uuid::Uuid;
+use rusqlite::types::{FromSqlError, FromSqlResult, ValueRef};
+
+stored_as!(
+    Uuid as Vec<u8>,
+    |val| val.as_bytes().to_vec(),
+    from_value = from_value
+);
+
+fn from_value(value: ValueRef<'_>) -> FromSqlResult<Uuid> {
+    match value {
+        ValueRef::Blob(bytes) => Uuid::from_slice(bytes).map_err(|e| FromSqlError::Other(e.into())),
+        ValueRef::Text(text) => {
+            Uuid::try_parse_ascii(text).map_err(|e| FromSqlError::Other(e.into()))
+        }
+        _ => Err(FromSqlError::InvalidType),
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    Expr, IntoExpr,
+    args::Query,
+    value::{DynTypedExpr, MyTyp, NumTyp},
+};
+
+/// Window functions are calculated after filtering, but before [Query::limit] and [Query::offset].
+///
+/// The resulting [Expr] can only be used in the selection of the query.
+/// Using it in a filter or aggregate results in an sqlite error.
+impl<'outer, 'inner, S> Query<'outer, 'inner, S> {
+    /// Calculate the running total of `expr` over all rows ordered by `order_by`.
+    ///
+    /// Rows with the same `order_by` value get a different running total,
+    /// which one comes first is unspecified.
+    ///
+    /// ```
+    /// # use rust_query::{IntoExpr, Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let mut totals = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     let total = rows.window_cumulative_sum(1.into_expr(), user.name());
+    ///     rows.into_vec((user.name(), total))
+    /// });
+    /// totals.sort();
+    /// assert_eq!(totals, [("Alice".to_owned(), 1), ("Bob".to_owned(), 2)]);
+    /// ```
+    pub fn window_cumulative_sum<T: NumTyp>(
+        &self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        order_by: impl IntoExpr<'inner, S>,
+    ) -> Expr<'inner, S, T> {
+        window(
+            "SUM(?) OVER (ORDER BY ? ROWS UNBOUNDED PRECEDING)",
+            [erase(expr), erase(order_by)],
+        )
+    }
+
+    /// Calculate the average of `expr` over the current row and the `window_size`
+    /// preceding rows, ordered by `order_by`.
+    ///
+    /// The first rows have fewer preceding rows, their average is calculated
+    /// over the rows that are available.
+    ///
+    /// ```
+    /// # use rust_query::{LocalClient, Table, migration::{Config, schema}};
+    /// #[schema(Schema)]
+    /// pub mod vN {
+    ///     pub struct Measurement {
+    ///         pub day: i64,
+    ///         pub value: f64,
+    ///     }
+    /// }
+    /// use v0::*;
+    ///
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&db);
+    /// for (day, value) in [(1, 1.0), (2, 3.0), (3, 8.0)] {
+    ///     txn.insert(Measurement { day, value }).unwrap();
+    /// }
+    /// let mut averages = txn.query(|rows| {
+    ///     let m = Measurement::join(rows);
+    ///     let avg = rows.window_moving_avg(m.value(), 1, m.day());
+    ///     rows.into_vec((m.day(), avg))
+    /// });
+    /// averages.sort_by_key(|x| x.0);
+    /// assert_eq!(averages, [(1, Some(1.0)), (2, Some(2.0)), (3, Some(5.5))]);
+    /// # }
+    /// ```
+    pub fn window_moving_avg<T: NumTyp>(
+        &self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        window_size: u64,
+        order_by: impl IntoExpr<'inner, S>,
+    ) -> Expr<'inner, S, Option<f64>> {
+        window(
+            format!(
+                "AVG(?) OVER (ORDER BY ? ROWS BETWEEN {window_size} PRECEDING AND CURRENT ROW)"
+            ),
+            [erase(expr), erase(order_by)],
+        )
+    }
+
+    /// Retrieve the value of `expr` from `offset` rows before the current row, ordered by `order_by`.
+    ///
+    /// The result is [None] if there are fewer than `offset` preceding rows.
+    /// Use [Expr::unwrap_or] to provide a default value in that case.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let mut pairs = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     let prev = rows.lag(user.name(), 1, user.name());
+    ///     rows.into_vec((user.name(), prev.unwrap_or("nobody")))
+    /// });
+    /// pairs.sort();
+    /// assert_eq!(pairs, [
+    ///     ("Alice".to_owned(), "nobody".to_owned()),
+    ///     ("Bob".to_owned(), "Alice".to_owned()),
+    /// ]);
+    /// ```
+    pub fn lag<T: MyTyp>(
+        &self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        offset: i64,
+        order_by: impl IntoExpr<'inner, S>,
+    ) -> Expr<'inner, S, Option<T>> {
+        window(
+            format!("LAG(?, {offset}) OVER (ORDER BY ?)"),
+            [erase(expr), erase(order_by)],
+        )
+    }
+
+    /// Retrieve the value of `expr` from `offset` rows after the current row, ordered by `order_by`.
+    ///
+    /// The result is [None] if there are fewer than `offset` following rows.
+    /// Use [Expr::unwrap_or] to provide a default value in that case.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// let mut pairs = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     let next = rows.lead(user.name(), 1, user.name());
+    ///     rows.into_vec((user.name(), next))
+    /// });
+    /// pairs.sort();
+    /// assert_eq!(pairs, [
+    ///     ("Alice".to_owned(), Some("Bob".to_owned())),
+    ///     ("Bob".to_owned(), None),
+    /// ]);
+    /// ```
+    pub fn lead<T: MyTyp>(
+        &self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        offset: i64,
+        order_by: impl IntoExpr<'inner, S>,
+    ) -> Expr<'inner, S, Option<T>> {
+        window(
+            format!("LEAD(?, {offset}) OVER (ORDER BY ?)"),
+            [erase(expr), erase(order_by)],
+        )
+    }
+
+    /// Retrieve the value of `expr` from the first row in the partition, ordered by `order_by`.
+    ///
+    /// Rows are partitioned by `partition_by`, which means that only rows with the same
+    /// `partition_by` value as the current row are considered.
+    ///
+    /// ```
+    /// # use rust_query::{LocalClient, Table, migration::{Config, schema}};
+    /// #[schema(Schema)]
+    /// pub mod vN {
+    ///     pub struct Track {
+    ///         pub album: String,
+    ///         pub position: i64,
+    ///         pub title: String,
+    ///     }
+    /// }
+    /// use v0::*;
+    ///
+    /// # fn main() {
+    /// # let mut client = LocalClient::try_new().unwrap();
+    /// # let db = client.migrator(Config::open_in_memory()).unwrap().finish().unwrap();
+    /// # let mut txn = client.transaction_mut(&db);
+    /// for (album, position, title) in [("A", 1, "Intro"), ("A", 2, "Outro"), ("B", 1, "Only")] {
+    ///     txn.insert(Track { album, position, title }).unwrap();
+    /// }
+    /// let mut tracks = txn.query(|rows| {
+    ///     let track = Track::join(rows);
+    ///     let first = rows.first_value(track.title(), track.position(), track.album());
+    ///     let last = rows.last_value(track.title(), track.position(), track.album());
+    ///     rows.into_vec((track.title(), (first, last)))
+    /// });
+    /// tracks.sort();
+    /// let expected = [("Intro", "Intro", "Outro"), ("Only", "Only", "Only"), ("Outro", "Intro", "Outro")];
+    /// let expected = expected.map(|(a, b, c)| (a.to_owned(), (b.to_owned(), c.to_owned())));
+    /// assert_eq!(tracks, expected);
+    /// # }
+    /// ```
+    pub fn first_value<T: MyTyp>(
+        &self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        order_by: impl IntoExpr<'inner, S>,
+        partition_by: impl IntoExpr<'inner, S>,
+    ) -> Expr<'inner, S, T> {
+        window(
+            "FIRST_VALUE(?) OVER (PARTITION BY ? ORDER BY ?)",
+            [erase(expr), erase(partition_by), erase(order_by)],
+        )
+    }
+
+    /// Retrieve the value of `expr` from the last row in the partition, ordered by `order_by`.
+    ///
+    /// This is the counterpart of [Query::first_value].
+    /// Unlike `LAST_VALUE` in sqlite, this always considers the whole partition instead of
+    /// only the rows up to the current row.
+    pub fn last_value<T: MyTyp>(
+        &self,
+        expr: impl IntoExpr<'inner, S, Typ = T>,
+        order_by: impl IntoExpr<'inner, S>,
+        partition_by: impl IntoExpr<'inner, S>,
+    ) -> Expr<'inner, S, T> {
+        window(
+            "LAST_VALUE(?) OVER (PARTITION BY ? ORDER BY ? \
+            ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING)",
+            [erase(expr), erase(partition_by), erase(order_by)],
+        )
+    }
+
+    /// Divide the rows, ordered by `order_by`, into `n` buckets of roughly equal size.
+    ///
+    /// The result is the number of the bucket that the current row is in, from 1 to `n`.
+    /// If the rows can not be divided equally, then the first buckets get one extra row.
+    ///
+    /// ```
+    /// # use rust_query::{Table, private::doctest::*};
+    /// # let mut client = get_client();
+    /// # let mut txn = get_txn(&mut client);
+    /// txn.insert(User { name: "Bob" }).unwrap();
+    /// txn.insert(User { name: "Charlie" }).unwrap();
+    /// let mut buckets = txn.query(|rows| {
+    ///     let user = User::join(rows);
+    ///     let bucket = rows.ntile(2, user.name());
+    ///     rows.into_vec((user.name(), bucket))
+    /// });
+    /// buckets.sort();
+    /// let buckets: Vec<_> = buckets.into_iter().map(|x| x.1).collect();
+    /// assert_eq!(buckets, [1, 1, 2]);
+    /// ```
+    pub fn ntile(&self, n: i64, order_by: impl IntoExpr<'inner, S>) -> Expr<'inner, S, i64> {
+        window(format!("NTILE({n}) OVER (ORDER BY ?)"), [erase(order_by)])
+    }
+}
+
+fn erase<'inner, S>(expr: impl IntoExpr<'inner, S>) -> DynTypedExpr {
+    expr.into_expr().inner.erase()
+}
+
+fn window<'inner, S, T: MyTyp>(
+    sql: impl Into<String>,
+    args: impl IntoIterator<Item = DynTypedExpr>,
+) -> Expr<'inner, S, T> {
+    let sql = sql.into();
+    let args: Vec<_> = args.into_iter().collect();
+    Expr::adhoc(move |b| sea_query::Expr::cust_with_exprs(sql.clone(), args.iter().map(|x| x.0(b))))
+}
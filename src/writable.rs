@@ -6,7 +6,7 @@ use crate::{
     Expr, IntoExpr, Table,
     alias::Field,
     ast::MySelect,
-    value::{DynTypedExpr, NumTyp},
+    value::{DynTypedExpr, Numeric},
 };
 
 /// Defines a column update.
@@ -37,7 +37,7 @@ impl<'t, S: 't, Typ: 't> Update<'t, S, Typ> {
     }
 }
 
-impl<'t, S: 't, Typ: NumTyp> Update<'t, S, Typ> {
+impl<'t, S: 't, Typ: Numeric<Output = Typ>> Update<'t, S, Typ> {
     /// Update the column value to the old value plus some new value.
     pub fn add(val: impl IntoExpr<'t, S, Typ = Typ>) -> Self {
         let val = val.into_expr();
@@ -0,0 +1,75 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use rust_query::{
+    BulkInserter, LocalClient, Table, TransactionMut,
+    migration::{Config, schema},
+};
+
+#[schema(Schema)]
+pub mod vN {
+    pub struct Event {
+        pub name: String,
+        pub seq: i64,
+    }
+}
+use v0::*;
+
+fn count(txn: &TransactionMut<Schema>) -> usize {
+    txn.query(|rows| {
+        let event = Event::join(rows);
+        rows.into_vec(event.seq()).len()
+    })
+}
+
+#[test]
+fn bulk_insert() {
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().init_stmt(
+        "CREATE TRIGGER reject_negative BEFORE INSERT ON event WHEN NEW.seq < 0 BEGIN
+            SELECT RAISE(ABORT, 'negative seq');
+        END;",
+    );
+    let db = client.migrator::<Schema>(config).unwrap().finish().unwrap();
+    let mut txn = client.transaction_mut(&db);
+
+    // only full batches are written before the inserter is flushed
+    let mut inserter = BulkInserter::new(&mut txn, 100);
+    for seq in 0..250 {
+        inserter.push(Event { name: "small", seq }).unwrap();
+    }
+    assert_eq!(count(inserter.transaction()), 200);
+
+    // the remaining rows are written on drop
+    drop(inserter);
+    assert_eq!(count(&txn), 250);
+
+    // batches larger than the sqlite compound select limit are split
+    let mut inserter = BulkInserter::new(&mut txn, 1200);
+    for seq in 0..1200 {
+        inserter.push(Event { name: "large", seq }).unwrap();
+    }
+    assert_eq!(count(inserter.transaction()), 1450);
+    drop(inserter);
+    assert_eq!(count(&txn), 1450);
+
+    let large: Vec<i64> = txn.query(|rows| {
+        let event = Event::join(rows);
+        rows.filter(event.name().eq("large"));
+        rows.into_vec_sorted(event.seq(), |seq| *seq)
+    });
+    assert_eq!(large, (0..1200).collect::<Vec<_>>());
+
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        BulkInserter::<_, Event>::new(&mut txn, 0);
+    }));
+    assert!(res.is_err(), "a batch size of zero is rejected");
+
+    // rows of a failed batch stay in the buffer, so flushing again retries them
+    let mut inserter = BulkInserter::new(&mut txn, 10);
+    for seq in [1, 2, -1] {
+        inserter.push(Event { name: "fail", seq }).unwrap();
+    }
+    assert!(inserter.flush().is_err());
+    assert!(inserter.flush().is_err());
+    assert_eq!(count(inserter.transaction()), 1450);
+}
@@ -3,6 +3,7 @@ mod schema;
 use std::fmt::Debug;
 
 use expect_test::expect_file;
+use rust_query::migration::Config;
 use rust_query::{
     Expr, IntoExpr, IntoSelectExt, LocalClient, Select, Table, TableRow, Transaction, Update,
     aggregate,
@@ -18,6 +19,41 @@ fn assert_dbg(mut val: &mut [impl Debug + PartialOrd], count: Option<usize>, fil
     expect_file![path].assert_debug_eq(&val);
 }
 
+#[test]
+fn query_plan() {
+    let mut client = LocalClient::try_new().unwrap();
+    let db = client
+        .migrator::<Schema>(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let db = client.transaction(&db);
+
+    let plan = db.query(|rows| {
+        let track = Track::join(rows);
+        rows.filter(track.name().eq("Balls to the Wall"));
+        rows.explain_plan(track.name())
+    });
+    assert!(!plan.uses_index::<Track>());
+
+    let plan = db.query(|rows| {
+        let album = Album::join(rows);
+        rows.filter(album.artist().name().eq("AC/DC"));
+        rows.explain_plan(album.title())
+    });
+    assert!(plan.uses_index::<Artist>(), "{}", plan.tree());
+    assert!(!plan.uses_index::<Album>(), "{}", plan.tree());
+
+    // the index of `playlist_track` starts with the name of `playlist`
+    let plan = db.query(|rows| {
+        let item = PlaylistTrack::join(rows);
+        rows.filter(item.playlist().name().eq("Music"));
+        rows.explain_plan(item.track().name())
+    });
+    assert!(plan.uses_index::<PlaylistTrack>(), "{}", plan.tree());
+    assert!(!plan.uses_index::<Playlist>(), "{}", plan.tree());
+}
+
 #[test]
 fn test_queries() {
     let mut client = LocalClient::try_new().unwrap();
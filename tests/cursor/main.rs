@@ -0,0 +1,86 @@
+use rust_query::{
+    Cursor, LocalClient, SortDir, Table, TransactionMut,
+    migration::{Config, schema},
+};
+
+#[schema(Schema)]
+pub mod vN {
+    pub struct Item {
+        pub rank: Option<i64>,
+        pub name: String,
+    }
+}
+use v0::*;
+
+fn page(
+    txn: &TransactionMut<Schema>,
+    cursor: Option<&Cursor<Option<i64>>>,
+) -> (Vec<String>, Option<Cursor<Option<i64>>>) {
+    txn.query(|rows| {
+        let item = Item::join(rows);
+        if let Some(cursor) = cursor {
+            rows.after_cursor(item.rank(), cursor);
+        }
+        rows.into_vec_with_cursor(item.rank(), item.name(), 1)
+    })
+}
+
+#[test]
+fn cursor() {
+    let mut client = LocalClient::try_new().unwrap();
+    let db = client
+        .migrator::<Schema>(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut txn = client.transaction_mut(&db);
+
+    for (rank, name) in [(Some(2), "b"), (None, "none"), (Some(1), "a")] {
+        txn.insert(Item { rank, name }).unwrap();
+    }
+
+    // rows with a `NULL` key come first and a `NULL` cursor continues after them
+    let mut names = vec![];
+    let mut cursor = None;
+    loop {
+        let (items, next) = page(&txn, cursor.as_ref());
+        names.extend(items);
+        let Some(next) = next else { break };
+        let text = next.to_string();
+        cursor = Some(text.parse().unwrap());
+        assert_eq!(cursor.as_ref(), Some(&next));
+    }
+    assert_eq!(names, ["none", "a", "b"]);
+
+    let first = page(&txn, None).1.unwrap();
+    assert_eq!(first.to_string(), "n");
+    assert_eq!(page(&txn, Some(&first)).0, ["a"]);
+
+    // an existing order and offset are replaced by the cursor key
+    let mut names = vec![];
+    let mut cursor: Option<Cursor<String>> = None;
+    loop {
+        let (items, next) = txn.query(|rows| {
+            let item = Item::join(rows);
+            rows.order_by(item.rank(), SortDir::Desc);
+            rows.offset(1);
+            if let Some(cursor) = &cursor {
+                rows.after_cursor(item.name(), cursor);
+            }
+            rows.into_vec_with_cursor(item.name(), item.name(), 2)
+        });
+        names.extend(items);
+        let Some(next) = next else { break };
+        cursor = Some(next);
+    }
+    assert_eq!(names, ["a", "b", "none"]);
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&first).unwrap();
+        assert_eq!(json, r#""n""#);
+        let back: Cursor<Option<i64>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, first);
+        assert!(serde_json::from_str::<Cursor<Option<i64>>>(r#""x""#).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use rust_query::{
+    Database, LocalClient, Table, TableRow, Update,
+    migration::{Config, schema},
+};
+
+#[schema(Schema)]
+pub mod vN {
+    pub struct Department {
+        pub name: String,
+    }
+    pub struct Employee {
+        pub name: String,
+        pub manager: Option<Employee>,
+        pub department: Department,
+    }
+}
+use v0::*;
+
+fn names(client: &mut LocalClient, db: &Database<Schema>) -> Vec<(String, String, Option<String>)> {
+    let txn = client.transaction(db);
+    let mut names: Vec<_> = txn
+        .query(|rows| {
+            let employee = Employee::join(rows);
+            rows.into_vec((
+                employee.name(),
+                (employee.department().name(), employee.manager()),
+            ))
+        })
+        .into_iter()
+        .map(|(name, (department, manager))| (name, department, manager.map(|m| txn.get(m).name)))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn dump_round_trip() {
+    let mut client = LocalClient::try_new().unwrap();
+    let db = client
+        .migrator::<Schema>(Config::open_in_memory())
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let mut txn = client.transaction_mut(&db);
+    let sales = txn.insert_ok(Department { name: "sales" });
+    // the employee is inserted before their manager, so the reference points to a later row
+    let employee = txn.insert_ok(Employee {
+        name: "Bob",
+        manager: None::<TableRow<_>>,
+        department: sales,
+    });
+    let manager = txn.insert_ok(Employee {
+        name: "Alice",
+        manager: None::<TableRow<_>>,
+        department: sales,
+    });
+    txn.update_ok(
+        employee,
+        Employee {
+            manager: Update::set(Some(manager)),
+            ..Default::default()
+        },
+    );
+    let dump = txn.dump_sql();
+    txn.commit();
+    assert!(dump.starts_with("PRAGMA defer_foreign_keys = ON;\n"));
+
+    let expected = [
+        ("Alice".to_owned(), "sales".to_owned(), None),
+        (
+            "Bob".to_owned(),
+            "sales".to_owned(),
+            Some("Alice".to_owned()),
+        ),
+    ];
+    assert_eq!(names(&mut client, &db), expected);
+
+    // only one database can be opened, so we restore the dump in the same database
+    let mut txn = client.transaction_mut(&db).downgrade();
+    let raw = txn.rusqlite_transaction();
+    raw.execute_batch(
+        "PRAGMA defer_foreign_keys = ON; DELETE FROM department; DELETE FROM employee;",
+    )
+    .unwrap();
+    raw.execute_batch(&dump).unwrap();
+    txn.commit();
+
+    assert_eq!(client.transaction(&db).dump_sql(), dump);
+    assert_eq!(names(&mut client, &db), expected);
+}
@@ -0,0 +1,39 @@
+use rust_query::{
+    LocalClient,
+    migration::{Config, schema},
+};
+
+#[schema(Schema)]
+#[version(0..=1)]
+pub mod vN {
+    pub struct Author {
+        pub name: String,
+    }
+    pub struct Book {
+        pub title: String,
+        pub author: Author,
+    }
+}
+
+#[test]
+#[should_panic(
+    expected = "the data migration violated a foreign key constraint referencing `author`"
+)]
+fn data_migration_checks_foreign_keys() {
+    let mut client = LocalClient::try_new().unwrap();
+    // the typed api can not break foreign keys, so a trigger is used to simulate a broken data migration
+    let config = Config::open_in_memory().init_stmt(
+        "INSERT INTO author (name) VALUES ('Alice');
+        INSERT INTO book (title, author) VALUES ('Rust', 1);
+        CREATE TRIGGER remove_alice AFTER INSERT ON author BEGIN
+            DELETE FROM author WHERE name = 'Alice';
+        END;",
+    );
+    let m = client.migrator(config).unwrap();
+    let _ = m.migrate_with_data(
+        |_| v0::migrate::Schema {},
+        |txn| {
+            txn.insert_ok(v1::Author { name: "Bob" });
+        },
+    );
+}
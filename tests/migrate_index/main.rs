@@ -0,0 +1,50 @@
+use rust_query::{
+    LocalClient, Table,
+    migration::{Config, schema},
+};
+
+#[schema(Schema)]
+#[version(0..=1)]
+pub mod vN {
+    pub struct Player {
+        pub name: String,
+        #[version(1..)]
+        #[index]
+        pub score: i64,
+    }
+}
+
+#[test]
+fn migrate_index() {
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().init_stmt("INSERT INTO player (name) VALUES ('Alice')");
+    let m = client.migrator(config).unwrap();
+    let m = m.migrate(|txn| v0::migrate::Schema {
+        player: txn.migrate_ok(|old: v0::Player!(name)| v0::migrate::Player {
+            score: old.name.len() as i64,
+        }),
+    });
+    let db = m.finish().unwrap();
+    let txn = client.transaction(&db);
+
+    let indexes = txn.query_raw(
+        "SELECT name, tbl_name FROM sqlite_master WHERE type = 'index' AND sql IS NOT NULL",
+        (),
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+    );
+    assert_eq!(
+        indexes.unwrap(),
+        [("player_score_index".to_owned(), "player".to_owned())]
+    );
+
+    let columns = txn.query_raw("PRAGMA index_info(player_score_index)", (), |row| {
+        row.get::<_, String>(2)
+    });
+    assert_eq!(columns.unwrap(), ["score"]);
+
+    let players = txn.query(|rows| {
+        let player = v1::Player::join(rows);
+        rows.into_vec((player.name(), player.score()))
+    });
+    assert_eq!(players, [("Alice".to_owned(), 5)]);
+}
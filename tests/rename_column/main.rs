@@ -0,0 +1,58 @@
+use rust_query::{
+    LocalClient, Table,
+    migration::{Config, schema},
+};
+
+#[schema(Schema)]
+#[version(0..=1)]
+pub mod vN {
+    pub struct Customer {
+        #[version(..1)]
+        pub name: String,
+        #[version(1..)]
+        #[rename_from = "name"]
+        pub full_name: String,
+        pub age: i64,
+        #[version(..1)]
+        pub note: Option<String>,
+        #[version(1..)]
+        #[rename_from = "note"]
+        pub remark: Option<String>,
+    }
+}
+
+#[test]
+fn rename_column() {
+    let mut client = LocalClient::try_new().unwrap();
+    let config = Config::open_in_memory().init_stmt(
+        "INSERT INTO customer (name, age, note) VALUES ('Alice', 30, 'vip'), ('Bob', 40, NULL)",
+    );
+    let m = client.migrator(config).unwrap();
+    let m = m.migrate(|txn| v0::migrate::Schema {
+        customer: txn.migrate_ok(|()| v0::migrate::Customer {}),
+    });
+    let db = m.finish().unwrap();
+    let txn = client.transaction(&db);
+
+    let mut customers = txn.query(|rows| {
+        let customer = v1::Customer::join(rows);
+        rows.into_vec((customer.full_name(), (customer.age(), customer.remark())))
+    });
+    customers.sort();
+    assert_eq!(
+        customers,
+        [
+            ("Alice".to_owned(), (30, Some("vip".to_owned()))),
+            ("Bob".to_owned(), (40, None)),
+        ]
+    );
+
+    let columns = txn.query_raw(
+        "SELECT name FROM pragma_table_info('customer')",
+        (),
+        |row| row.get::<_, String>(0),
+    );
+    let mut columns = columns.unwrap();
+    columns.sort();
+    assert_eq!(columns, ["age", "full_name", "id", "remark"]);
+}